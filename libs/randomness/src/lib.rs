@@ -0,0 +1,450 @@
+//! Randomness sources shared by turf combat, character rolls and loot drops.
+//!
+//! A program stores a [`RandomnessRequest`] when an action is launched and
+//! derives the seed later with [`RandomnessRequest::resolve`]:
+//!
+//! - `CommitReveal`: the player and the game server each commit to
+//!   `hash(secret)` up front; the seed mixes both secrets once revealed, so
+//!   neither side can pick the outcome alone. Either side can still refuse
+//!   to reveal once it knows the result, so callers must treat a missing
+//!   reveal as a loss for whoever benefits from withholding it.
+//! - `Commit`: only the player commits; callers must mix the hash of a slot
+//!   after the commit into `domain` so the player can't grind the outcome.
+//! - `Vrf`: the seed is read from an oracle-owned result account whose layout
+//!   is described by [`VrfResult`] and can be stubbed in local tests.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::{hash, hashv};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum RandomnessRequest {
+    CommitReveal {
+        player_commitment: [u8; 32],
+        server_commitment: [u8; 32],
+    },
+    Vrf {
+        result_account: Pubkey,
+    },
+    Commit {
+        commitment: [u8; 32],
+    },
+}
+
+/// Revealed secrets; `Commit` requests only read `player_secret`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CommitRevealSecrets {
+    pub player_secret: [u8; 32],
+    pub server_secret: [u8; 32],
+}
+
+/// Oracle result account: 8 byte header, 32 byte request key, 8 byte
+/// fulfilled slot (0 while pending), 32 byte result
+pub struct VrfResult {
+    pub request: Pubkey,
+    pub fulfilled_slot: u64,
+    pub result: [u8; 32],
+}
+
+impl VrfResult {
+    pub const LEN: usize = 8 + 32 + 8 + 32;
+
+    pub fn load(account: &AccountInfo, oracle_program: &Pubkey) -> Result<Self> {
+        require_keys_eq!(*account.owner, *oracle_program, RandomnessError::InvalidOracleAccount);
+        let data = account.try_borrow_data()?;
+        require!(data.len() >= Self::LEN, RandomnessError::InvalidOracleAccount);
+
+        let mut result = [0u8; 32];
+        result.copy_from_slice(&data[48..80]);
+        Ok(Self {
+            request: Pubkey::try_from(&data[8..40]).unwrap(),
+            fulfilled_slot: u64::from_le_bytes(data[40..48].try_into().unwrap()),
+            result,
+        })
+    }
+
+    pub fn is_fulfilled(&self) -> bool {
+        self.fulfilled_slot > 0
+    }
+}
+
+impl RandomnessRequest {
+    /// Check a VRF request at launch: the result account must belong to the
+    /// oracle, be bound to `request` and not be fulfilled yet
+    pub fn validate_vrf(
+        &self,
+        result_account: Option<&AccountInfo>,
+        oracle_program: &Pubkey,
+        request: &Pubkey,
+    ) -> Result<()> {
+        if let RandomnessRequest::Vrf { result_account: expected } = self {
+            let account = result_account.ok_or(RandomnessError::MissingVrfResult)?;
+            require_keys_eq!(account.key(), *expected, RandomnessError::InvalidOracleAccount);
+            let vrf = VrfResult::load(account, oracle_program)?;
+            require_keys_eq!(vrf.request, *request, RandomnessError::RequestMismatch);
+            require!(!vrf.is_fulfilled(), RandomnessError::AlreadyFulfilled);
+        }
+        Ok(())
+    }
+
+    /// Derive the seed for this request, mixing in `domain` so the same
+    /// secrets or oracle result never produce the same roll twice
+    pub fn resolve(
+        &self,
+        secrets: Option<&CommitRevealSecrets>,
+        result_account: Option<&AccountInfo>,
+        oracle_program: &Pubkey,
+        domain: &[&[u8]],
+    ) -> Result<[u8; 32]> {
+        let entropy = match self {
+            RandomnessRequest::CommitReveal {
+                player_commitment,
+                server_commitment,
+            } => {
+                let secrets = secrets.ok_or(RandomnessError::MissingReveal)?;
+                require!(
+                    commitment(&secrets.player_secret) == *player_commitment
+                        && commitment(&secrets.server_secret) == *server_commitment,
+                    RandomnessError::InvalidReveal
+                );
+                hashv(&[&secrets.player_secret, &secrets.server_secret]).to_bytes()
+            }
+            RandomnessRequest::Vrf { result_account: expected } => {
+                let account = result_account.ok_or(RandomnessError::MissingVrfResult)?;
+                require_keys_eq!(account.key(), *expected, RandomnessError::InvalidOracleAccount);
+                let vrf = VrfResult::load(account, oracle_program)?;
+                require!(vrf.is_fulfilled(), RandomnessError::NotFulfilled);
+                vrf.result
+            }
+            RandomnessRequest::Commit { commitment: expected } => {
+                let secrets = secrets.ok_or(RandomnessError::MissingReveal)?;
+                require!(
+                    commitment(&secrets.player_secret) == *expected,
+                    RandomnessError::InvalidReveal
+                );
+                secrets.player_secret
+            }
+        };
+
+        let mut inputs: Vec<&[u8]> = domain.to_vec();
+        inputs.push(&entropy);
+        Ok(hashv(&inputs).to_bytes())
+    }
+
+    /// Whether the randomness is still withheld: no secrets revealed, or a
+    /// VRF result not fulfilled yet. Lets callers tell a timeout apart from
+    /// a bad reveal; VRF requests must still pass their result account.
+    pub fn is_pending(
+        &self,
+        secrets: Option<&CommitRevealSecrets>,
+        result_account: Option<&AccountInfo>,
+        oracle_program: &Pubkey,
+    ) -> Result<bool> {
+        match self {
+            RandomnessRequest::CommitReveal { .. } | RandomnessRequest::Commit { .. } => {
+                Ok(secrets.is_none())
+            }
+            RandomnessRequest::Vrf { result_account: expected } => {
+                let account = result_account.ok_or(RandomnessError::MissingVrfResult)?;
+                require_keys_eq!(account.key(), *expected, RandomnessError::InvalidOracleAccount);
+                Ok(!VrfResult::load(account, oracle_program)?.is_fulfilled())
+            }
+        }
+    }
+}
+
+/// Commitment published before a secret is revealed
+pub fn commitment(secret: &[u8; 32]) -> [u8; 32] {
+    hash(secret).to_bytes()
+}
+
+/// Hash of `slot` from the SlotHashes sysvar, or `None` once it has aged out
+pub fn slot_hash(slot_hashes: &AccountInfo, slot: u64) -> Result<Option<[u8; 32]>> {
+    const ENTRY_LEN: usize = 8 + 32;
+    let data = slot_hashes.try_borrow_data()?;
+    let len = u64::from_le_bytes(data[..8].try_into().unwrap()) as usize;
+
+    // Entries are sorted by slot, newest first
+    let (mut low, mut high) = (0, len);
+    while low < high {
+        let mid = (low + high) / 2;
+        let offset = 8 + mid * ENTRY_LEN;
+        let entry_slot = u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+        match entry_slot.cmp(&slot) {
+            std::cmp::Ordering::Equal => {
+                let mut hash = [0u8; 32];
+                hash.copy_from_slice(&data[offset + 8..offset + ENTRY_LEN]);
+                return Ok(Some(hash));
+            }
+            std::cmp::Ordering::Greater => low = mid + 1,
+            std::cmp::Ordering::Less => high = mid,
+        }
+    }
+
+    Ok(None)
+}
+
+/// Little-endian u64 read from `seed` at `offset`
+pub fn seed_u64(seed: &[u8; 32], offset: usize) -> u64 {
+    u64::from_le_bytes(seed[offset..offset + 8].try_into().unwrap())
+}
+
+#[error_code(offset = 9000)]
+pub enum RandomnessError {
+    #[msg("Commit-reveal secrets were not provided")]
+    MissingReveal,
+    #[msg("Revealed secrets do not match their commitments")]
+    InvalidReveal,
+    #[msg("VRF result account was not provided")]
+    MissingVrfResult,
+    #[msg("VRF result account is not owned by the configured oracle")]
+    InvalidOracleAccount,
+    #[msg("VRF result is bound to a different request")]
+    RequestMismatch,
+    #[msg("VRF result was fulfilled before the request")]
+    AlreadyFulfilled,
+    #[msg("VRF result is not fulfilled yet")]
+    NotFulfilled,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORACLE: Pubkey = Pubkey::new_from_array([7; 32]);
+    const REQUEST: Pubkey = Pubkey::new_from_array([9; 32]);
+
+    fn vrf_data(request: &Pubkey, fulfilled_slot: u64, result: [u8; 32]) -> Vec<u8> {
+        let mut data = vec![0u8; 8];
+        data.extend_from_slice(request.as_ref());
+        data.extend_from_slice(&fulfilled_slot.to_le_bytes());
+        data.extend_from_slice(&result);
+        data
+    }
+
+    fn with_account<T>(
+        key: &Pubkey,
+        owner: &Pubkey,
+        data: &mut [u8],
+        f: impl FnOnce(&AccountInfo) -> T,
+    ) -> T {
+        let mut lamports = 0;
+        let account = AccountInfo::new(key, false, false, &mut lamports, data, owner, false, 0);
+        f(&account)
+    }
+
+    fn assert_error<T>(result: Result<T>, expected: RandomnessError) {
+        match result {
+            Ok(_) => panic!("expected {:?}", expected),
+            Err(err) => assert_eq!(err, expected.into()),
+        }
+    }
+
+    #[test]
+    fn commitment_is_sha256_of_secret() {
+        let secret = [1u8; 32];
+        assert_eq!(commitment(&secret), hash(&secret).to_bytes());
+        assert_ne!(commitment(&secret), commitment(&[2u8; 32]));
+    }
+
+    #[test]
+    fn seed_u64_reads_little_endian_at_offset() {
+        let mut seed = [0u8; 32];
+        seed[8..16].copy_from_slice(&0x0102_0304_0506_0708u64.to_le_bytes());
+        assert_eq!(seed_u64(&seed, 0), 0);
+        assert_eq!(seed_u64(&seed, 8), 0x0102_0304_0506_0708);
+        assert_eq!(seed_u64(&[0xff; 32], 24), u64::MAX);
+    }
+
+    #[test]
+    fn vrf_result_load_parses_layout() {
+        let key = Pubkey::new_unique();
+        let mut data = vrf_data(&REQUEST, 42, [3; 32]);
+        let vrf = with_account(&key, &ORACLE, &mut data, |account| {
+            VrfResult::load(account, &ORACLE).unwrap()
+        });
+        assert_eq!(vrf.request, REQUEST);
+        assert_eq!(vrf.fulfilled_slot, 42);
+        assert_eq!(vrf.result, [3; 32]);
+        assert!(vrf.is_fulfilled());
+    }
+
+    #[test]
+    fn vrf_result_load_rejects_wrong_owner_and_short_data() {
+        let key = Pubkey::new_unique();
+        let mut data = vrf_data(&REQUEST, 0, [0; 32]);
+        with_account(&key, &Pubkey::new_unique(), &mut data, |account| {
+            assert_error(VrfResult::load(account, &ORACLE), RandomnessError::InvalidOracleAccount)
+        });
+        let mut short = vec![0u8; VrfResult::LEN - 1];
+        with_account(&key, &ORACLE, &mut short, |account| {
+            assert_error(VrfResult::load(account, &ORACLE), RandomnessError::InvalidOracleAccount)
+        });
+    }
+
+    #[test]
+    fn validate_vrf_accepts_pending_bound_request() {
+        let key = Pubkey::new_unique();
+        let request = RandomnessRequest::Vrf { result_account: key };
+        let mut data = vrf_data(&REQUEST, 0, [0; 32]);
+        with_account(&key, &ORACLE, &mut data, |account| {
+            request.validate_vrf(Some(account), &ORACLE, &REQUEST).unwrap()
+        });
+    }
+
+    #[test]
+    fn validate_vrf_error_paths() {
+        let key = Pubkey::new_unique();
+        let request = RandomnessRequest::Vrf { result_account: key };
+        assert_error(
+            request.validate_vrf(None, &ORACLE, &REQUEST),
+            RandomnessError::MissingVrfResult,
+        );
+
+        let other = Pubkey::new_unique();
+        let mut data = vrf_data(&REQUEST, 0, [0; 32]);
+        with_account(&other, &ORACLE, &mut data, |account| {
+            assert_error(
+                request.validate_vrf(Some(account), &ORACLE, &REQUEST),
+                RandomnessError::InvalidOracleAccount,
+            )
+        });
+
+        let mut data = vrf_data(&Pubkey::new_unique(), 0, [0; 32]);
+        with_account(&key, &ORACLE, &mut data, |account| {
+            assert_error(
+                request.validate_vrf(Some(account), &ORACLE, &REQUEST),
+                RandomnessError::RequestMismatch,
+            )
+        });
+
+        let mut data = vrf_data(&REQUEST, 5, [0; 32]);
+        with_account(&key, &ORACLE, &mut data, |account| {
+            assert_error(
+                request.validate_vrf(Some(account), &ORACLE, &REQUEST),
+                RandomnessError::AlreadyFulfilled,
+            )
+        });
+    }
+
+    #[test]
+    fn resolve_commit_reveal_checks_both_secrets() {
+        let secrets = CommitRevealSecrets {
+            player_secret: [1; 32],
+            server_secret: [2; 32],
+        };
+        let request = RandomnessRequest::CommitReveal {
+            player_commitment: commitment(&secrets.player_secret),
+            server_commitment: commitment(&secrets.server_secret),
+        };
+        let seed = request.resolve(Some(&secrets), None, &ORACLE, &[b"domain"]).unwrap();
+        let entropy = hashv(&[&secrets.player_secret, &secrets.server_secret]).to_bytes();
+        assert_eq!(seed, hashv(&[b"domain", &entropy]).to_bytes());
+        assert_ne!(seed, request.resolve(Some(&secrets), None, &ORACLE, &[b"other"]).unwrap());
+
+        assert_error(
+            request.resolve(None, None, &ORACLE, &[]),
+            RandomnessError::MissingReveal,
+        );
+        let wrong = CommitRevealSecrets {
+            server_secret: [3; 32],
+            ..secrets
+        };
+        assert_error(
+            request.resolve(Some(&wrong), None, &ORACLE, &[]),
+            RandomnessError::InvalidReveal,
+        );
+    }
+
+    #[test]
+    fn resolve_commit_uses_player_secret_only() {
+        let secret = [4u8; 32];
+        let request = RandomnessRequest::Commit {
+            commitment: commitment(&secret),
+        };
+        let reveal = |server_secret| CommitRevealSecrets {
+            player_secret: secret,
+            server_secret,
+        };
+        let seed = request.resolve(Some(&reveal([0; 32])), None, &ORACLE, &[b"slot"]).unwrap();
+        assert_eq!(seed, hashv(&[b"slot", &secret]).to_bytes());
+        assert_eq!(
+            seed,
+            request.resolve(Some(&reveal([8; 32])), None, &ORACLE, &[b"slot"]).unwrap()
+        );
+
+        let wrong = CommitRevealSecrets {
+            player_secret: [5; 32],
+            server_secret: [0; 32],
+        };
+        assert_error(
+            request.resolve(Some(&wrong), None, &ORACLE, &[]),
+            RandomnessError::InvalidReveal,
+        );
+    }
+
+    #[test]
+    fn resolve_vrf_requires_fulfilled_result() {
+        let key = Pubkey::new_unique();
+        let request = RandomnessRequest::Vrf { result_account: key };
+
+        let mut data = vrf_data(&REQUEST, 0, [0; 32]);
+        with_account(&key, &ORACLE, &mut data, |account| {
+            assert_error(
+                request.resolve(None, Some(account), &ORACLE, &[]),
+                RandomnessError::NotFulfilled,
+            )
+        });
+
+        let mut data = vrf_data(&REQUEST, 10, [6; 32]);
+        let seed = with_account(&key, &ORACLE, &mut data, |account| {
+            request.resolve(None, Some(account), &ORACLE, &[b"domain"]).unwrap()
+        });
+        assert_eq!(seed, hashv(&[b"domain", &[6u8; 32]]).to_bytes());
+    }
+
+    #[test]
+    fn is_pending_tracks_withheld_randomness() {
+        let secrets = CommitRevealSecrets {
+            player_secret: [1; 32],
+            server_secret: [2; 32],
+        };
+        let request = RandomnessRequest::CommitReveal {
+            player_commitment: commitment(&secrets.player_secret),
+            server_commitment: commitment(&secrets.server_secret),
+        };
+        assert!(request.is_pending(None, None, &ORACLE).unwrap());
+        assert!(!request.is_pending(Some(&secrets), None, &ORACLE).unwrap());
+
+        let key = Pubkey::new_unique();
+        let request = RandomnessRequest::Vrf { result_account: key };
+        assert_error(
+            request.is_pending(None, None, &ORACLE),
+            RandomnessError::MissingVrfResult,
+        );
+        let mut data = vrf_data(&REQUEST, 0, [0; 32]);
+        with_account(&key, &ORACLE, &mut data, |account| {
+            assert!(request.is_pending(None, Some(account), &ORACLE).unwrap())
+        });
+        let mut data = vrf_data(&REQUEST, 10, [6; 32]);
+        with_account(&key, &ORACLE, &mut data, |account| {
+            assert!(!request.is_pending(None, Some(account), &ORACLE).unwrap())
+        });
+    }
+
+    #[test]
+    fn slot_hash_finds_entries_newest_first() {
+        let entries: [(u64, [u8; 32]); 3] = [(30, [3; 32]), (20, [2; 32]), (10, [1; 32])];
+        let mut data = (entries.len() as u64).to_le_bytes().to_vec();
+        for (slot, hash) in entries {
+            data.extend_from_slice(&slot.to_le_bytes());
+            data.extend_from_slice(&hash);
+        }
+        let key = Pubkey::new_unique();
+        with_account(&key, &Pubkey::default(), &mut data, |account| {
+            assert_eq!(slot_hash(account, 20).unwrap(), Some([2; 32]));
+            assert_eq!(slot_hash(account, 30).unwrap(), Some([3; 32]));
+            assert_eq!(slot_hash(account, 10).unwrap(), Some([1; 32]));
+            assert_eq!(slot_hash(account, 15).unwrap(), None);
+            assert_eq!(slot_hash(account, 40).unwrap(), None);
+        });
+    }
+}
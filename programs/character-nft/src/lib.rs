@@ -1,15 +1,32 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{
+    prelude::*,
+    solana_program::sysvar::slot_hashes,
+    system_program,
+};
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount},
+    token::{self, Burn, CloseAccount, Mint, MintTo, Token, TokenAccount, Transfer},
 };
 use mpl_token_metadata::{
-    instruction::{create_master_edition_v3, create_metadata_accounts_v3},
-    state::{Creator, DataV2, Collection},
+    instruction::{
+        create_master_edition_v3, create_metadata_accounts_v3, update_metadata_accounts_v2,
+        verify_collection,
+    },
+    state::{Creator, DataV2, Collection, Metadata, TokenMetadataAccount},
 };
+use mafia_randomness::{seed_u64, slot_hash, CommitRevealSecrets, RandomnessRequest};
+use mob_token::program::MobToken;
 
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 
+/// Published drop weights: 40% / 30% / 20% / 8% / 1.8% / 0.2%
+pub const DEFAULT_RARITY_WEIGHTS: [u16; 6] = [4000, 3000, 2000, 800, 180, 20];
+pub const RARITY_WEIGHT_TOTAL: u16 = 10_000;
+const MAX_XP_SOURCES: usize = 10;
+const MAX_NAME_LENGTH: usize = 32; // Metaplex name limit
+const MAX_URI_LENGTH: usize = 200; // Metaplex URI limit
+const MAX_URI_TEMPLATE_LENGTH: usize = 150; // Leaves room for the longest placeholder values
+
 #[program]
 pub mod character_nft {
     use super::*;
@@ -23,12 +40,65 @@ pub mod character_nft {
         config.max_supply = 10_000;
         config.mint_price = 500_000_000; // 0.5 SOL in lamports
         config.is_active = true;
+        config.set_default_extensions(ctx.accounts.authority.key(), ctx.accounts.mob_mint.key());
         
         msg!("Character NFT program initialized");
         Ok(())
     }
 
-    /// Mint a new character NFT
+    /// Grow a config account created before the current layout, giving the
+    /// fields added since their initialize defaults (admin only)
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        let config_info = ctx.accounts.config.to_account_info();
+        let authority = {
+            let data = config_info.try_borrow_data()?;
+            Pubkey::try_from(&data[8..40]).map_err(|_| ErrorCode::Unauthorized)?
+        };
+        require!(ctx.accounts.authority.key() == authority, ErrorCode::Unauthorized);
+
+        grow_account(
+            &config_info,
+            8 + Config::INIT_SPACE,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        let mut config = Config::try_deserialize(&mut &config_info.try_borrow_data()?[..])?;
+        config.set_default_extensions(authority, ctx.accounts.mob_mint.key());
+        config.try_serialize(&mut &mut config_info.try_borrow_mut_data()?[..])?;
+
+        msg!("Config migrated to the current layout");
+        Ok(())
+    }
+
+    /// Grow a character account created before the current layout and record
+    /// its base name, as shown in its metadata (admin only)
+    pub fn migrate_character(ctx: Context<MigrateCharacter>, name: String) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.config.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(name.len() <= MAX_NAME_LENGTH, ErrorCode::NameTooLong);
+
+        let character_info = ctx.accounts.character.to_account_info();
+        grow_account(
+            &character_info,
+            8 + Character::INIT_SPACE,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        // Fields added since the account was created read back as zero
+        let mut character = Character::try_deserialize(&mut &character_info.try_borrow_data()?[..])?;
+        character.name = name;
+        character.try_serialize(&mut &mut character_info.try_borrow_mut_data()?[..])?;
+
+        msg!("Character {} migrated to the current layout", character.mint);
+        Ok(())
+    }
+
+    /// Mint a character NFT with explicit rarity and stats (admin only);
+    /// players mint through commit_mint and reveal_character
     pub fn mint_character(
         ctx: Context<MintCharacter>,
         name: String,
@@ -39,6 +109,10 @@ pub mod character_nft {
     ) -> Result<()> {
         let config = &mut ctx.accounts.config;
         
+        require!(
+            ctx.accounts.payer.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
         require!(config.is_active, ErrorCode::MintingInactive);
         require!(config.total_minted < config.max_supply, ErrorCode::MaxSupplyReached);
         require!(name.len() <= 32, ErrorCode::NameTooLong);
@@ -48,124 +122,457 @@ pub mod character_nft {
         // Validate stats based on rarity
         stats.validate_for_rarity(&rarity)?;
 
-        // Create metadata
-        let creators = vec![
-            Creator {
-                address: config.authority,
-                verified: true,
-                share: 100,
-            }
-        ];
+        collect_mint_price(
+            ctx.accounts.payer.to_account_info(),
+            ctx.accounts.proceeds_vault.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            config.mint_price,
+        )?;
 
-        let collection = Some(Collection {
-            verified: false,
-            key: config.collection_mint,
-        });
+        let collection = new_character_collection(
+            config,
+            ctx.accounts.collection_mint.as_ref(),
+            ctx.accounts.collection_metadata.as_ref(),
+            ctx.accounts.collection_master_edition.as_ref(),
+        )?;
 
-        let data = DataV2 {
-            name,
+        let nft_accounts = CharacterNftAccounts {
+            mint: ctx.accounts.mint.to_account_info(),
+            mint_authority: ctx.accounts.mint_authority.to_account_info(),
+            token_account: ctx.accounts.token_account.to_account_info(),
+            metadata: ctx.accounts.metadata.to_account_info(),
+            master_edition: ctx.accounts.master_edition.to_account_info(),
+            payer: ctx.accounts.payer.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+            token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+        };
+        mint_character_token(&nft_accounts, ctx.bumps.mint_authority)?;
+        create_character_nft(
+            nft_accounts,
+            config,
+            Some(config.collection_mint),
+            name.clone(),
             symbol,
             uri,
-            seller_fee_basis_points: 500, // 5% royalty
-            creators: Some(creators),
-            collection,
-            uses: None,
-        };
+            ctx.bumps.mint_authority,
+        )?;
+        if let Some(collection) = &collection {
+            verify_in_collection(
+                ctx.accounts.mint.key(),
+                &ctx.accounts.metadata.to_account_info(),
+                &ctx.accounts.mint_authority.to_account_info(),
+                &ctx.accounts.payer.to_account_info(),
+                collection,
+                ctx.bumps.mint_authority,
+            )?;
+        }
 
-        // Create metadata account
-        let metadata_seeds = &[
-            b"metadata",
-            ctx.accounts.token_metadata_program.key().as_ref(),
-            ctx.accounts.mint.key().as_ref(),
-        ];
-        let (metadata_pda, _) = Pubkey::find_program_address(
-            metadata_seeds,
-            &ctx.accounts.token_metadata_program.key(),
-        );
+        // Store character data
+        let character = &mut ctx.accounts.character;
+        character.mint = ctx.accounts.mint.key();
+        character.name = name;
+        character.owner = ctx.accounts.owner.key();
+        character.rarity = rarity;
+        character.stats = stats;
+        character.level = 1;
+        character.experience = 0;
+        character.last_mission_time = Clock::get()?.unix_timestamp;
+        character.is_staked = false;
+        character.xp_day_start = 0;
+        character.xp_granted_today = 0;
+        character.allocated_points = [0; 5];
+        character.staked_at = 0;
+        character.last_yield_time = 0;
+        character.accrued_yield = 0;
 
-        let create_metadata_ix = create_metadata_accounts_v3(
-            ctx.accounts.token_metadata_program.key(),
-            metadata_pda,
-            ctx.accounts.mint.key(),
-            ctx.accounts.mint_authority.key(),
-            ctx.accounts.payer.key(),
-            ctx.accounts.mint_authority.key(),
-            data,
-            true,
-            true,
-            None,
-        );
+        config.total_minted += 1;
+        config.total_proceeds += config.mint_price;
 
-        anchor_lang::solana_program::program::invoke_signed(
-            &create_metadata_ix,
-            &[
-                ctx.accounts.metadata.to_account_info(),
-                ctx.accounts.mint.to_account_info(),
-                ctx.accounts.mint_authority.to_account_info(),
-                ctx.accounts.payer.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-                ctx.accounts.rent.to_account_info(),
-            ],
-            &[&[
-                b"mint_authority",
-                &[ctx.bumps.mint_authority],
-            ]],
+        emit!(CharacterSold {
+            mint: character.mint,
+            buyer: ctx.accounts.payer.key(),
+            owner: character.owner,
+            price: config.mint_price,
+            total_minted: config.total_minted,
+            timestamp: character.last_mission_time,
+        });
+
+        msg!("Character NFT minted successfully");
+        Ok(())
+    }
+
+    /// Pay for a character and commit to a secret whose reveal rolls its
+    /// rarity and stats. The commitment is sha256(secret).
+    pub fn commit_character(ctx: Context<CommitCharacter>, commitment: [u8; 32]) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        require!(config.is_active, ErrorCode::MintingInactive);
+        require!(config.total_minted < config.max_supply, ErrorCode::MaxSupplyReached);
+
+        collect_mint_price(
+            ctx.accounts.payer.to_account_info(),
+            ctx.accounts.proceeds_vault.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            config.mint_price,
         )?;
+        config.total_proceeds += config.mint_price;
 
-        // Create master edition
-        let edition_seeds = &[
-            b"metadata",
-            ctx.accounts.token_metadata_program.key().as_ref(),
-            ctx.accounts.mint.key().as_ref(),
-            b"edition",
-        ];
-        let (edition_pda, _) = Pubkey::find_program_address(
-            edition_seeds,
-            &ctx.accounts.token_metadata_program.key(),
-        );
+        let clock = Clock::get()?;
+        let mint_commitment = &mut ctx.accounts.mint_commitment;
+        mint_commitment.payer = ctx.accounts.payer.key();
+        mint_commitment.owner = ctx.accounts.owner.key();
+        mint_commitment.commitment = commitment;
+        mint_commitment.commit_slot = clock.slot;
+        mint_commitment.price_paid = config.mint_price;
+        mint_commitment.rarity_weights = config.rarity_weights;
+
+        emit!(MintCommitted {
+            payer: mint_commitment.payer,
+            owner: mint_commitment.owner,
+            commitment,
+            commit_slot: mint_commitment.commit_slot,
+            price: mint_commitment.price_paid,
+            timestamp: clock.unix_timestamp,
+        });
 
-        let create_edition_ix = create_master_edition_v3(
-            ctx.accounts.token_metadata_program.key(),
-            edition_pda,
-            ctx.accounts.mint.key(),
-            ctx.accounts.mint_authority.key(),
-            ctx.accounts.mint_authority.key(),
-            metadata_pda,
-            ctx.accounts.payer.key(),
-            Some(0), // Max supply of 0 means unlimited prints
+        msg!("Character mint committed at slot {}", mint_commitment.commit_slot);
+        Ok(())
+    }
+
+    /// Reveal a committed secret and mint a character whose rarity and stats
+    /// are rolled from the mafia_randomness seed over (commitment account,
+    /// commit slot, hash of the slot after the commit, secret). The mint key
+    /// is chosen at reveal time, so it stays out of the seed.
+    pub fn reveal_character(
+        ctx: Context<RevealCharacter>,
+        name: String,
+        symbol: String,
+        uri: String,
+        secret: [u8; 32],
+    ) -> Result<()> {
+        let mint_commitment = &ctx.accounts.mint_commitment;
+        let current_slot = Clock::get()?.slot;
+
+        require!(name.len() <= 32, ErrorCode::NameTooLong);
+        require!(symbol.len() <= 10, ErrorCode::SymbolTooLong);
+        require!(uri.len() <= 200, ErrorCode::UriTooLong);
+        require!(
+            ctx.accounts.config.total_minted < ctx.accounts.config.max_supply,
+            ErrorCode::MaxSupplyReached
         );
+        let seed_slot = mint_commitment.commit_slot + 1;
+        require!(current_slot > seed_slot, ErrorCode::RevealTooEarly);
 
-        anchor_lang::solana_program::program::invoke_signed(
-            &create_edition_ix,
+        let slot_hash = slot_hash(&ctx.accounts.slot_hashes, seed_slot)?
+            .ok_or(ErrorCode::RevealExpired)?;
+        let randomness = RandomnessRequest::Commit {
+            commitment: mint_commitment.commitment,
+        };
+        let seed = randomness.resolve(
+            Some(&CommitRevealSecrets {
+                player_secret: secret,
+                server_secret: [0; 32],
+            }),
+            None,
+            &Pubkey::default(),
             &[
-                ctx.accounts.master_edition.to_account_info(),
-                ctx.accounts.mint.to_account_info(),
-                ctx.accounts.mint_authority.to_account_info(),
-                ctx.accounts.payer.to_account_info(),
-                ctx.accounts.metadata.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-                ctx.accounts.rent.to_account_info(),
+                mint_commitment.key().as_ref(),
+                &mint_commitment.commit_slot.to_le_bytes(),
+                &slot_hash,
             ],
-            &[&[
-                b"mint_authority",
-                &[ctx.bumps.mint_authority],
-            ]],
         )?;
+        let rarity = CharacterRarity::roll(&seed, &mint_commitment.rarity_weights);
+        let stats = CharacterStats::roll(&seed, &rarity);
+        stats.validate_for_rarity(&rarity)?;
+
+        let collection = new_character_collection(
+            &ctx.accounts.config,
+            ctx.accounts.collection_mint.as_ref(),
+            ctx.accounts.collection_metadata.as_ref(),
+            ctx.accounts.collection_master_edition.as_ref(),
+        )?;
+
+        let nft_accounts = CharacterNftAccounts {
+            mint: ctx.accounts.mint.to_account_info(),
+            mint_authority: ctx.accounts.mint_authority.to_account_info(),
+            token_account: ctx.accounts.token_account.to_account_info(),
+            metadata: ctx.accounts.metadata.to_account_info(),
+            master_edition: ctx.accounts.master_edition.to_account_info(),
+            payer: ctx.accounts.payer.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+            token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+        };
+        mint_character_token(&nft_accounts, ctx.bumps.mint_authority)?;
+        create_character_nft(
+            nft_accounts,
+            &ctx.accounts.config,
+            Some(ctx.accounts.config.collection_mint),
+            name.clone(),
+            symbol,
+            uri,
+            ctx.bumps.mint_authority,
+        )?;
+        if let Some(collection) = &collection {
+            verify_in_collection(
+                ctx.accounts.mint.key(),
+                &ctx.accounts.metadata.to_account_info(),
+                &ctx.accounts.mint_authority.to_account_info(),
+                &ctx.accounts.payer.to_account_info(),
+                collection,
+                ctx.bumps.mint_authority,
+            )?;
+        }
 
-        // Store character data
         let character = &mut ctx.accounts.character;
         character.mint = ctx.accounts.mint.key();
-        character.owner = ctx.accounts.owner.key();
+        character.name = name;
+        character.owner = mint_commitment.owner;
         character.rarity = rarity;
         character.stats = stats;
         character.level = 1;
         character.experience = 0;
         character.last_mission_time = Clock::get()?.unix_timestamp;
         character.is_staked = false;
+        character.xp_day_start = 0;
+        character.xp_granted_today = 0;
+        character.allocated_points = [0; 5];
+        character.staked_at = 0;
+        character.last_yield_time = 0;
+        character.accrued_yield = 0;
 
+        let config = &mut ctx.accounts.config;
         config.total_minted += 1;
 
-        msg!("Character NFT minted successfully");
+        emit!(CharacterSold {
+            mint: character.mint,
+            buyer: mint_commitment.payer,
+            owner: character.owner,
+            price: mint_commitment.price_paid,
+            total_minted: config.total_minted,
+            timestamp: character.last_mission_time,
+        });
+
+        emit!(CharacterRolled {
+            mint: character.mint,
+            mint_commitment: mint_commitment.key(),
+            secret,
+            commit_slot: mint_commitment.commit_slot,
+            seed_slot,
+            slot_hash,
+            seed,
+            rarity_weights: mint_commitment.rarity_weights,
+            rarity,
+            stats,
+        });
+
+        msg!("Character revealed as {:?}", rarity);
+        Ok(())
+    }
+
+    /// Close an unrevealed commitment, e.g. after its slot hash expired.
+    /// The mint price is not refunded, so abandoning a bad roll gains nothing.
+    pub fn cancel_commitment(ctx: Context<CancelCommitment>) -> Result<()> {
+        emit!(CommitmentCancelled {
+            payer: ctx.accounts.payer.key(),
+            commit_slot: ctx.accounts.mint_commitment.commit_slot,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Character mint commitment cancelled");
+        Ok(())
+    }
+
+    /// Withdraw accumulated mint proceeds (admin only)
+    pub fn withdraw_proceeds(ctx: Context<WithdrawProceeds>, amount: u64) -> Result<()> {
+        let config = &ctx.accounts.config;
+
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        // The vault keeps its rent-exempt minimum so it is never reaped
+        let vault = &ctx.accounts.proceeds_vault;
+        let available = vault
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(0));
+        require!(amount <= available, ErrorCode::InsufficientProceeds);
+
+        let seeds: &[&[u8]] = &[b"proceeds", &[ctx.bumps.proceeds_vault]];
+        let signer = &[seeds];
+
+        let cpi_accounts = system_program::Transfer {
+            from: vault.to_account_info(),
+            to: ctx.accounts.destination.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        system_program::transfer(cpi_ctx, amount)?;
+
+        emit!(ProceedsWithdrawn {
+            authority: ctx.accounts.authority.key(),
+            destination: ctx.accounts.destination.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Withdrew {} lamports of mint proceeds", amount);
+        Ok(())
+    }
+
+    /// Update program configuration (admin only)
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        new_mint_price: Option<u64>,
+        new_rarity_weights: Option<[u16; 6]>,
+        new_game_server: Option<Pubkey>,
+        new_daily_xp_cap: Option<u64>,
+        new_mob_mint: Option<Pubkey>,
+        new_respec_cost: Option<u64>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+
+        if let Some(price) = new_mint_price {
+            emit!(MintPriceUpdated {
+                old_price: config.mint_price,
+                new_price: price,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+            config.mint_price = price;
+        }
+
+        if let Some(weights) = new_rarity_weights {
+            require!(
+                weights.iter().map(|w| *w as u32).sum::<u32>() == RARITY_WEIGHT_TOTAL as u32,
+                ErrorCode::InvalidRarityWeights
+            );
+            config.rarity_weights = weights;
+        }
+
+        if let Some(game_server) = new_game_server {
+            config.game_server = game_server;
+        }
+
+        if let Some(cap) = new_daily_xp_cap {
+            config.daily_xp_cap = cap;
+        }
+
+        if let Some(mob_mint) = new_mob_mint {
+            config.mob_mint = mob_mint;
+        }
+
+        if let Some(cost) = new_respec_cost {
+            config.respec_cost = cost;
+        }
+
+        msg!("Character NFT config updated");
+        Ok(())
+    }
+
+    /// Authorize a mission or turf program PDA to grant experience (admin only)
+    pub fn add_xp_source(ctx: Context<ManageXpSources>, source: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(!config.xp_sources.contains(&source), ErrorCode::XpSourceAlreadyExists);
+        require!(config.xp_sources.len() < MAX_XP_SOURCES, ErrorCode::TooManyXpSources);
+
+        config.xp_sources.push(source);
+
+        emit!(XpSourceAdded {
+            source,
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Experience source added: {}", source);
+        Ok(())
+    }
+
+    /// Revoke an experience source (admin only)
+    pub fn remove_xp_source(ctx: Context<ManageXpSources>, source: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+
+        let index = config
+            .xp_sources
+            .iter()
+            .position(|key| *key == source)
+            .ok_or(ErrorCode::XpSourceNotFound)?;
+        config.xp_sources.remove(index);
+
+        emit!(XpSourceRemoved {
+            source,
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Experience source removed: {}", source);
+        Ok(())
+    }
+
+    /// Grant experience to a character (game server or registered sources only).
+    /// Grants beyond the character's daily cap are trimmed to what is left.
+    pub fn grant_experience(
+        ctx: Context<GrantExperience>,
+        amount: u64,
+        reason: ExperienceReason,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let character = &mut ctx.accounts.character;
+        let source = ctx.accounts.source.key();
+        let current_time = Clock::get()?.unix_timestamp;
+
+        require!(
+            source == config.game_server || config.xp_sources.contains(&source),
+            ErrorCode::UnauthorizedXpSource
+        );
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        if current_time - character.xp_day_start >= 86400 { // 24 hours
+            character.xp_day_start = current_time;
+            character.xp_granted_today = 0;
+        }
+
+        let remaining_today = config.daily_xp_cap.saturating_sub(character.xp_granted_today);
+        require!(remaining_today > 0, ErrorCode::DailyXpCapReached);
+        let granted = amount.min(remaining_today);
+
+        character.experience += granted;
+        character.xp_granted_today += granted;
+        character.last_mission_time = current_time;
+
+        emit!(ExperienceGranted {
+            mint: character.mint,
+            owner: character.owner,
+            source,
+            amount: granted,
+            reason,
+            total_experience: character.experience,
+            timestamp: current_time,
+        });
+
+        msg!("Granted {} experience for {:?}", granted, reason);
         Ok(())
     }
 
@@ -174,6 +581,7 @@ pub mod character_nft {
         let character = &mut ctx.accounts.character;
         
         require!(character.owner == ctx.accounts.owner.key(), ErrorCode::NotOwner);
+        require!(!character.is_staked, ErrorCode::CharacterStaked);
         require!(character.level < 100, ErrorCode::MaxLevelReached);
         
         let required_exp = calculate_required_experience(character.level);
@@ -193,119 +601,1438 @@ pub mod character_nft {
         };
         
         character.stats.available_points += stat_points;
+
+        refresh_templated_metadata(
+            &ctx.accounts.config,
+            character,
+            ctx.accounts.metadata.as_ref(),
+            ctx.accounts.mint_authority.as_ref(),
+            ctx.accounts.token_metadata_program.as_ref(),
+            ctx.bumps.mint_authority,
+        )?;
         
         msg!("Character leveled up to level {}", character.level);
         Ok(())
     }
 
-    /// Merge/burn characters to create higher tier
-    pub fn merge_characters(ctx: Context<MergeCharacters>) -> Result<()> {
-        // Implementation for burning 3 characters to create next tier
-        // This would involve complex logic for rarity upgrades
-        msg!("Character merge functionality - to be implemented");
+    /// Spend available stat points, capped per stat by rarity and level
+    pub fn allocate_stats(ctx: Context<AllocateStats>, allocation: StatAllocation) -> Result<()> {
+        let character = &mut ctx.accounts.character;
+
+        require!(character.owner == ctx.accounts.owner.key(), ErrorCode::NotOwner);
+        require!(!character.is_staked, ErrorCode::CharacterStaked);
+
+        let points = allocation.as_array();
+        let spent: u16 = points.iter().map(|p| *p as u16).sum();
+        require!(spent > 0, ErrorCode::InvalidAmount);
+        require!(
+            spent <= character.stats.available_points as u16,
+            ErrorCode::InsufficientStatPoints
+        );
+
+        let cap = character.rarity.stat_cap(character.level);
+        let mut stats = character.stats.as_array();
+        for (i, stat) in stats.iter_mut().enumerate() {
+            let raised = *stat as u16 + points[i] as u16;
+            require!(raised <= cap as u16, ErrorCode::StatCapExceeded);
+            *stat = raised as u8;
+            character.allocated_points[i] += points[i];
+        }
+        character.stats.set_array(stats);
+        character.stats.available_points -= spent as u8;
+
+        emit!(StatsAllocated {
+            mint: character.mint,
+            owner: character.owner,
+            allocation,
+            available_points: character.stats.available_points,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        refresh_templated_metadata(
+            &ctx.accounts.config,
+            character,
+            ctx.accounts.metadata.as_ref(),
+            ctx.accounts.mint_authority.as_ref(),
+            ctx.accounts.token_metadata_program.as_ref(),
+            ctx.bumps.mint_authority,
+        )?;
+
+        msg!("Allocated {} stat points", spent);
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + Config::INIT_SPACE,
-        seeds = [b"config"],
-        bump
-    )]
-    pub config: Account<'info, Config>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    pub collection_mint: Account<'info, Mint>,
-    pub system_program: Program<'info, System>,
-}
+    /// Refund every allocated stat point by burning the configured MOB respec cost
+    pub fn respec_stats(ctx: Context<RespecStats>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let character = &mut ctx.accounts.character;
 
-#[derive(Accounts)]
-pub struct MintCharacter<'info> {
-    #[account(
-        mut,
-        seeds = [b"config"],
-        bump
-    )]
-    pub config: Account<'info, Config>,
-    
-    #[account(
-        init,
-        payer = payer,
-        mint::decimals = 0,
-        mint::authority = mint_authority,
-        mint::freeze_authority = mint_authority,
-    )]
-    pub mint: Account<'info, Mint>,
-    
-    #[account(
-        seeds = [b"mint_authority"],
-        bump
-    )]
-    /// CHECK: PDA used as mint authority
-    pub mint_authority: UncheckedAccount<'info>,
-    
-    #[account(
-        init,
-        payer = payer,
-        associated_token::mint = mint,
-        associated_token::authority = owner,
-    )]
-    pub token_account: Account<'info, TokenAccount>,
-    
-    #[account(
-        init,
-        payer = payer,
-        space = 8 + Character::INIT_SPACE,
-        seeds = [b"character", mint.key().as_ref()],
-        bump
-    )]
-    pub character: Account<'info, Character>,
-    
-    /// CHECK: Metadata account
-    #[account(mut)]
-    pub metadata: UncheckedAccount<'info>,
-    
-    /// CHECK: Master edition account
-    #[account(mut)]
-    pub master_edition: UncheckedAccount<'info>,
-    
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    
-    /// CHECK: Owner of the NFT
-    pub owner: UncheckedAccount<'info>,
-    
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
+        require!(character.owner == ctx.accounts.owner.key(), ErrorCode::NotOwner);
+        require!(!character.is_staked, ErrorCode::CharacterStaked);
+
+        let refunded: u16 = character.allocated_points.iter().map(|p| *p as u16).sum();
+        require!(refunded > 0, ErrorCode::NothingToRespec);
+        let available_points = (character.stats.available_points as u16 + refunded)
+            .try_into()
+            .map_err(|_| ErrorCode::TooManyUnspentPoints)?;
+
+        if config.respec_cost > 0 {
+            let cpi_accounts = Burn {
+                mint: ctx.accounts.mob_mint.to_account_info(),
+                from: ctx.accounts.owner_mob_account.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            token::burn(CpiContext::new(cpi_program, cpi_accounts), config.respec_cost)?;
+        }
+
+        let mut stats = character.stats.as_array();
+        for (stat, allocated) in stats.iter_mut().zip(character.allocated_points.iter()) {
+            *stat -= allocated;
+        }
+        character.stats.set_array(stats);
+        character.stats.available_points = available_points;
+        character.allocated_points = [0; 5];
+
+        emit!(StatsRespecced {
+            mint: character.mint,
+            owner: character.owner,
+            refunded_points: refunded,
+            mob_burned: config.respec_cost,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Respecced {} stat points", refunded);
+        Ok(())
+    }
+
+    /// Set the daily MOB yield of a staked level 1 Common character (admin only)
+    pub fn set_staking_yield_rate(ctx: Context<UpdateConfig>, staking_yield_rate: u64) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+
+        config.staking_yield_rate = staking_yield_rate;
+
+        msg!("Character staking yield set to {} per day", staking_yield_rate);
+        Ok(())
+    }
+
+    /// Lock a character NFT in the program vault to start accruing MOB yield
+    pub fn stake_character(ctx: Context<StakeCharacter>) -> Result<()> {
+        let character = &mut ctx.accounts.character;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        require!(character.owner == ctx.accounts.owner.key(), ErrorCode::NotOwner);
+        require!(!character.is_staked, ErrorCode::CharacterStaked);
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.owner_token_account.to_account_info(),
+            to: ctx.accounts.vault_token_account.to_account_info(),
+            authority: ctx.accounts.owner.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(CpiContext::new(cpi_program, cpi_accounts), 1)?;
+
+        character.is_staked = true;
+        character.staked_at = current_time;
+        character.last_yield_time = current_time;
+
+        emit!(CharacterStaked {
+            mint: character.mint,
+            owner: character.owner,
+            timestamp: current_time,
+        });
+
+        msg!("Character {} staked", character.mint);
+        Ok(())
+    }
+
+    /// Return a staked character NFT to its owner; accrued yield stays claimable
+    pub fn unstake_character(ctx: Context<UnstakeCharacter>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let character = &mut ctx.accounts.character;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        require!(character.owner == ctx.accounts.owner.key(), ErrorCode::NotOwner);
+        require!(character.is_staked, ErrorCode::CharacterNotStaked);
+
+        character.accrue_yield(config.staking_yield_rate, current_time);
+
+        let seeds: &[&[u8]] = &[b"staking_authority", &[ctx.bumps.staking_authority]];
+        let signer = &[seeds];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault_token_account.to_account_info(),
+            to: ctx.accounts.owner_token_account.to_account_info(),
+            authority: ctx.accounts.staking_authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(
+            CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer),
+            1,
+        )?;
+
+        // Return the vault's rent to the owner
+        let cpi_accounts = CloseAccount {
+            account: ctx.accounts.vault_token_account.to_account_info(),
+            destination: ctx.accounts.owner.to_account_info(),
+            authority: ctx.accounts.staking_authority.to_account_info(),
+        };
+        token::close_account(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer))?;
+
+        character.is_staked = false;
+
+        emit!(CharacterUnstaked {
+            mint: character.mint,
+            owner: character.owner,
+            staked_duration: current_time - character.staked_at,
+            accrued_yield: character.accrued_yield,
+            timestamp: current_time,
+        });
+
+        msg!("Character {} unstaked", character.mint);
+        Ok(())
+    }
+
+    /// Claim accrued staking yield as MOB via mob_token::mint_reward.
+    /// The staking authority PDA must be registered as a mob_token issuer.
+    pub fn claim_character_yield(ctx: Context<ClaimCharacterYield>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let character = &mut ctx.accounts.character;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        require!(character.owner == ctx.accounts.owner.key(), ErrorCode::NotOwner);
+
+        character.accrue_yield(config.staking_yield_rate, current_time);
+        let amount = character.accrued_yield.min(mob_token::MAX_STAKING_REWARD);
+        require!(amount > 0, ErrorCode::NoYieldToClaim);
+
+        let seeds: &[&[u8]] = &[b"staking_authority", &[ctx.bumps.staking_authority]];
+        let signer = &[seeds];
+
+        let cpi_accounts = mob_token::cpi::accounts::MintReward {
+            config: ctx.accounts.mob_config.to_account_info(),
+            mint_authority: ctx.accounts.mob_mint_authority.to_account_info(),
+            mint: ctx.accounts.mob_mint.to_account_info(),
+            player_data: ctx.accounts.player_data.to_account_info(),
+            recipient_token_account: ctx.accounts.owner_mob_account.to_account_info(),
+            recipient: ctx.accounts.owner.to_account_info(),
+            issuer: ctx.accounts.staking_authority.to_account_info(),
+            payer: ctx.accounts.owner.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.mob_token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        mob_token::cpi::mint_reward(cpi_ctx, amount, mob_token::RewardType::Staking)?;
+
+        character.accrued_yield -= amount;
+
+        emit!(CharacterYieldClaimed {
+            mint: character.mint,
+            owner: character.owner,
+            amount,
+            remaining_yield: character.accrued_yield,
+            timestamp: current_time,
+        });
+
+        msg!("Claimed {} MOB of character staking yield", amount);
+        Ok(())
+    }
+
+    /// Create the collection NFT from `Config.collection_mint` (admin only, once).
+    /// The mint authority PDA holds the NFT and is the collection authority.
+    pub fn create_collection(
+        ctx: Context<CreateCollection>,
+        name: String,
+        symbol: String,
+        uri: String,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(!config.collection_created, ErrorCode::CollectionAlreadyCreated);
+        require!(name.len() <= 32, ErrorCode::NameTooLong);
+        require!(symbol.len() <= 10, ErrorCode::SymbolTooLong);
+        require!(uri.len() <= 200, ErrorCode::UriTooLong);
+
+        let nft_accounts = CharacterNftAccounts {
+            mint: ctx.accounts.collection_mint.to_account_info(),
+            mint_authority: ctx.accounts.mint_authority.to_account_info(),
+            token_account: ctx.accounts.collection_token_account.to_account_info(),
+            metadata: ctx.accounts.collection_metadata.to_account_info(),
+            master_edition: ctx.accounts.collection_master_edition.to_account_info(),
+            payer: ctx.accounts.authority.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+            token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+        };
+        mint_character_token(&nft_accounts, ctx.bumps.mint_authority)?;
+        create_character_nft(
+            nft_accounts,
+            config,
+            None,
+            name,
+            symbol,
+            uri,
+            ctx.bumps.mint_authority,
+        )?;
+
+        config.collection_created = true;
+
+        emit!(CollectionCreated {
+            collection_mint: config.collection_mint,
+            metadata: ctx.accounts.collection_metadata.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Character collection created: {}", config.collection_mint);
+        Ok(())
+    }
+
+    /// Verify a character's membership in the collection, signed by the mint
+    /// authority PDA. Permissionless, for characters minted before the collection
+    /// was created.
+    pub fn verify_character_collection(ctx: Context<VerifyCharacterCollection>) -> Result<()> {
+        let config = &ctx.accounts.config;
+
+        require!(config.collection_created, ErrorCode::CollectionNotCreated);
+
+        let collection = CollectionAccounts {
+            mint: ctx.accounts.collection_mint.to_account_info(),
+            metadata: ctx.accounts.collection_metadata.to_account_info(),
+            master_edition: ctx.accounts.collection_master_edition.to_account_info(),
+        };
+        verify_in_collection(
+            ctx.accounts.character.mint,
+            &ctx.accounts.metadata.to_account_info(),
+            &ctx.accounts.mint_authority.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &collection,
+            ctx.bumps.mint_authority,
+        )?;
+
+        msg!("Character {} verified in collection", ctx.accounts.character.mint);
+        Ok(())
+    }
+
+    /// Set the metadata URI template (admin only). Supports `{mint}`, `{level}`,
+    /// `{rarity}` and `{stats}` placeholders; empty disables metadata updates.
+    pub fn set_uri_template(ctx: Context<UpdateConfig>, uri_template: String) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(uri_template.len() <= MAX_URI_TEMPLATE_LENGTH, ErrorCode::UriTooLong);
+
+        config.uri_template = uri_template;
+
+        msg!("Character metadata URI template updated");
+        Ok(())
+    }
+
+    /// Rewrite a character's metadata name and URI from its on-chain state,
+    /// signed by the mint authority PDA as update authority. Permissionless, for
+    /// characters changed before the template was set.
+    pub fn update_character_metadata(ctx: Context<UpdateCharacterMetadata>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let character = &ctx.accounts.character;
+
+        require!(!config.uri_template.is_empty(), ErrorCode::UriTemplateNotSet);
+
+        update_metadata_from_state(
+            config,
+            character,
+            &ctx.accounts.metadata.to_account_info(),
+            &ctx.accounts.mint_authority.to_account_info(),
+            ctx.bumps.mint_authority,
+        )?;
+
+        msg!("Character {} metadata updated", character.mint);
+        Ok(())
+    }
+
+    /// Merge/burn three characters of the same rarity to create the next tier
+    pub fn merge_characters(
+        ctx: Context<MergeCharacters>,
+        name: String,
+        symbol: String,
+        uri: String,
+    ) -> Result<()> {
+        let owner = ctx.accounts.owner.key();
+        let character_a = &ctx.accounts.character_a;
+        let character_b = &ctx.accounts.character_b;
+        let character_c = &ctx.accounts.character_c;
+
+        require!(ctx.accounts.config.is_active, ErrorCode::MintingInactive);
+        require!(name.len() <= 32, ErrorCode::NameTooLong);
+        require!(symbol.len() <= 10, ErrorCode::SymbolTooLong);
+        require!(uri.len() <= 200, ErrorCode::UriTooLong);
+        require!(
+            character_a.mint != character_b.mint
+                && character_a.mint != character_c.mint
+                && character_b.mint != character_c.mint,
+            ErrorCode::DuplicateMergeInput
+        );
+        require!(
+            character_a.owner == owner && character_b.owner == owner && character_c.owner == owner,
+            ErrorCode::NotOwner
+        );
+        require!(
+            !character_a.is_staked && !character_b.is_staked && !character_c.is_staked,
+            ErrorCode::CharacterStaked
+        );
+        require!(
+            character_a.rarity == character_b.rarity && character_a.rarity == character_c.rarity,
+            ErrorCode::RarityMismatch
+        );
+
+        let rarity = character_a.rarity;
+        let new_rarity = rarity.next().ok_or(ErrorCode::MaxRarityReached)?;
+        let mut stats = CharacterStats::merged(
+            [
+                &character_a.base_stats(),
+                &character_b.base_stats(),
+                &character_c.base_stats(),
+            ],
+            &rarity,
+        );
+        stats.validate_for_rarity(&new_rarity)?;
+
+        // Allocated and unspent points of the inputs carry over as unspent points
+        let carried_points =
+            character_a.stat_points() + character_b.stat_points() + character_c.stat_points();
+        stats.available_points = carried_points
+            .try_into()
+            .map_err(|_| ErrorCode::TooManyPointsToMerge)?;
+
+        let burned_mints = [character_a.mint, character_b.mint, character_c.mint];
+
+        // Burn the input NFTs and close their token accounts
+        let inputs = [
+            (&ctx.accounts.mint_a, &ctx.accounts.token_account_a),
+            (&ctx.accounts.mint_b, &ctx.accounts.token_account_b),
+            (&ctx.accounts.mint_c, &ctx.accounts.token_account_c),
+        ];
+        for (mint, token_account) in inputs {
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_accounts = Burn {
+                mint: mint.to_account_info(),
+                from: token_account.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            };
+            token::burn(CpiContext::new(cpi_program.clone(), cpi_accounts), 1)?;
+
+            let cpi_accounts = CloseAccount {
+                account: token_account.to_account_info(),
+                destination: ctx.accounts.owner.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            };
+            token::close_account(CpiContext::new(cpi_program, cpi_accounts))?;
+        }
+
+        let character = &mut ctx.accounts.character;
+        character.mint = ctx.accounts.mint.key();
+        character.owner = owner;
+        character.rarity = new_rarity;
+        character.stats = stats;
+        character.level = 1;
+        character.experience = 0;
+        character.last_mission_time = Clock::get()?.unix_timestamp;
+        character.is_staked = false;
+        character.xp_day_start = 0;
+        character.xp_granted_today = 0;
+        character.allocated_points = [0; 5];
+        character.staked_at = 0;
+        character.last_yield_time = 0;
+        character.accrued_yield = 0;
+        character.name = name;
+
+        // With a URI template set, the new metadata follows it like any other update
+        let config = &ctx.accounts.config;
+        let (metadata_name, metadata_uri) = if config.uri_template.is_empty() {
+            (character.name.clone(), uri)
+        } else {
+            let uri = render_metadata_uri(&config.uri_template, character);
+            require!(uri.len() <= MAX_URI_LENGTH, ErrorCode::UriTooLong);
+            (leveled_name(&character.name, character.level), uri)
+        };
+
+        let collection = new_character_collection(
+            config,
+            ctx.accounts.collection_mint.as_ref(),
+            ctx.accounts.collection_metadata.as_ref(),
+            ctx.accounts.collection_master_edition.as_ref(),
+        )?;
+
+        // Mint the upgraded character
+        let nft_accounts = CharacterNftAccounts {
+            mint: ctx.accounts.mint.to_account_info(),
+            mint_authority: ctx.accounts.mint_authority.to_account_info(),
+            token_account: ctx.accounts.token_account.to_account_info(),
+            metadata: ctx.accounts.metadata.to_account_info(),
+            master_edition: ctx.accounts.master_edition.to_account_info(),
+            payer: ctx.accounts.owner.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+            token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+        };
+        mint_character_token(&nft_accounts, ctx.bumps.mint_authority)?;
+        create_character_nft(
+            nft_accounts,
+            config,
+            Some(config.collection_mint),
+            metadata_name,
+            symbol,
+            metadata_uri,
+            ctx.bumps.mint_authority,
+        )?;
+        if let Some(collection) = &collection {
+            verify_in_collection(
+                ctx.accounts.mint.key(),
+                &ctx.accounts.metadata.to_account_info(),
+                &ctx.accounts.mint_authority.to_account_info(),
+                &ctx.accounts.owner.to_account_info(),
+                collection,
+                ctx.bumps.mint_authority,
+            )?;
+        }
+
+        let character = &ctx.accounts.character;
+        emit!(CharactersMerged {
+            owner,
+            burned_mints,
+            new_mint: character.mint,
+            new_rarity,
+            timestamp: character.last_mission_time,
+        });
+
+        msg!("Merged three {:?} characters into a {:?}", rarity, new_rarity);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub collection_mint: Account<'info, Mint>,
+    pub mob_mint: Account<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    /// CHECK: Config in a possibly outdated layout; the authority is read from its raw data
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        owner = crate::ID
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub mob_mint: Account<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateCharacter<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Character in a possibly outdated layout
+    #[account(
+        mut,
+        seeds = [b"character", mint.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub character: UncheckedAccount<'info>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MintCharacter<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        init,
+        payer = payer,
+        mint::decimals = 0,
+        mint::authority = mint_authority,
+        mint::freeze_authority = mint_authority,
+    )]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"mint_authority"],
+        bump
+    )]
+    /// CHECK: PDA used as mint authority and collection authority
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = owner,
+    )]
+    pub token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Character::INIT_SPACE,
+        seeds = [b"character", mint.key().as_ref()],
+        bump
+    )]
+    pub character: Account<'info, Character>,
+    
+    /// CHECK: Metadata account
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+    
+    /// CHECK: Master edition account
+    #[account(mut)]
+    pub master_edition: UncheckedAccount<'info>,
+    
+    /// CHECK: Collection mint, required once the collection is created
+    #[account(address = config.collection_mint)]
+    pub collection_mint: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Collection metadata, required once the collection is created
+    #[account(
+        seeds = [b"metadata", mpl_token_metadata::ID.as_ref(), config.collection_mint.as_ref()],
+        bump,
+        seeds::program = mpl_token_metadata::ID
+    )]
+    pub collection_metadata: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Collection master edition, required once the collection is created
+    #[account(
+        seeds = [
+            b"metadata",
+            mpl_token_metadata::ID.as_ref(),
+            config.collection_mint.as_ref(),
+            b"edition"
+        ],
+        bump,
+        seeds::program = mpl_token_metadata::ID
+    )]
+    pub collection_master_edition: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        mut,
+        seeds = [b"proceeds"],
+        bump
+    )]
+    pub proceeds_vault: SystemAccount<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    /// CHECK: Owner of the NFT
+    pub owner: UncheckedAccount<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+    
+    /// CHECK: Metaplex token metadata program
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CommitCharacter<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + MintCommitment::INIT_SPACE,
+        seeds = [b"commitment", payer.key().as_ref()],
+        bump
+    )]
+    pub mint_commitment: Account<'info, MintCommitment>,
+
+    #[account(
+        mut,
+        seeds = [b"proceeds"],
+        bump
+    )]
+    pub proceeds_vault: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Owner of the NFT once revealed
+    pub owner: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevealCharacter<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"commitment", payer.key().as_ref()],
+        bump
+    )]
+    pub mint_commitment: Account<'info, MintCommitment>,
+
+    #[account(
+        init,
+        payer = payer,
+        mint::decimals = 0,
+        mint::authority = mint_authority,
+        mint::freeze_authority = mint_authority,
+    )]
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"mint_authority"],
+        bump
+    )]
+    /// CHECK: PDA used as mint authority and collection authority
+    pub mint_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = owner,
+    )]
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Character::INIT_SPACE,
+        seeds = [b"character", mint.key().as_ref()],
+        bump
+    )]
+    pub character: Account<'info, Character>,
+
+    /// CHECK: Metadata account
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Master edition account
+    #[account(mut)]
+    pub master_edition: UncheckedAccount<'info>,
+
+    /// CHECK: Collection mint, required once the collection is created
+    #[account(address = config.collection_mint)]
+    pub collection_mint: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Collection metadata, required once the collection is created
+    #[account(
+        seeds = [b"metadata", mpl_token_metadata::ID.as_ref(), config.collection_mint.as_ref()],
+        bump,
+        seeds::program = mpl_token_metadata::ID
+    )]
+    pub collection_metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Collection master edition, required once the collection is created
+    #[account(
+        seeds = [
+            b"metadata",
+            mpl_token_metadata::ID.as_ref(),
+            config.collection_mint.as_ref(),
+            b"edition"
+        ],
+        bump,
+        seeds::program = mpl_token_metadata::ID
+    )]
+    pub collection_master_edition: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Owner recorded in the commitment
+    #[account(address = mint_commitment.owner)]
+    pub owner: UncheckedAccount<'info>,
+
+    /// CHECK: SlotHashes sysvar, parsed manually
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+
+    /// CHECK: Metaplex token metadata program
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelCommitment<'info> {
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"commitment", payer.key().as_ref()],
+        bump
+    )]
+    pub mint_commitment: Account<'info, MintCommitment>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawProceeds<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"proceeds"],
+        bump
+    )]
+    pub proceeds_vault: SystemAccount<'info>,
+
+    /// CHECK: Any account chosen by the authority to receive proceeds
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ManageXpSources<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GrantExperience<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"character", character.mint.as_ref()],
+        bump
+    )]
+    pub character: Account<'info, Character>,
+
+    /// Game server key or a registered mission/turf program PDA
+    pub source: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct LevelUp<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"character", character.mint.as_ref()],
+        bump
+    )]
+    pub character: Account<'info, Character>,
+
+    /// CHECK: Character metadata, required once a URI template is set
+    #[account(
+        mut,
+        seeds = [b"metadata", mpl_token_metadata::ID.as_ref(), character.mint.as_ref()],
+        bump,
+        seeds::program = mpl_token_metadata::ID
+    )]
+    pub metadata: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        seeds = [b"mint_authority"],
+        bump
+    )]
+    /// CHECK: PDA used as metadata update authority, required once a URI template is set
+    pub mint_authority: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex token metadata program, required once a URI template is set
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: Option<UncheckedAccount<'info>>,
     
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AllocateStats<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"character", character.mint.as_ref()],
+        bump
+    )]
+    pub character: Account<'info, Character>,
+
+    /// CHECK: Character metadata, required once a URI template is set
+    #[account(
+        mut,
+        seeds = [b"metadata", mpl_token_metadata::ID.as_ref(), character.mint.as_ref()],
+        bump,
+        seeds::program = mpl_token_metadata::ID
+    )]
+    pub metadata: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        seeds = [b"mint_authority"],
+        bump
+    )]
+    /// CHECK: PDA used as metadata update authority, required once a URI template is set
+    pub mint_authority: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex token metadata program, required once a URI template is set
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: Option<UncheckedAccount<'info>>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RespecStats<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"character", character.mint.as_ref()],
+        bump
+    )]
+    pub character: Account<'info, Character>,
+
+    #[account(
+        mut,
+        address = config.mob_mint
+    )]
+    pub mob_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = mob_mint,
+        token::authority = owner,
+    )]
+    pub owner_mob_account: Account<'info, TokenAccount>,
+
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct StakeCharacter<'info> {
+    #[account(
+        mut,
+        seeds = [b"character", character.mint.as_ref()],
+        bump
+    )]
+    pub character: Account<'info, Character>,
+
+    #[account(address = character.mint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = owner,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = owner,
+        token::mint = mint,
+        token::authority = staking_authority,
+        seeds = [b"character_vault", mint.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"staking_authority"],
+        bump
+    )]
+    /// CHECK: PDA that owns staked character NFTs and issues their MOB yield
+    pub staking_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct UnstakeCharacter<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"character", character.mint.as_ref()],
+        bump
+    )]
+    pub character: Account<'info, Character>,
+
+    #[account(address = character.mint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"character_vault", mint.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"staking_authority"],
+        bump
+    )]
+    /// CHECK: PDA that owns staked character NFTs and issues their MOB yield
+    pub staking_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint,
+        associated_token::authority = owner,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimCharacterYield<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"character", character.mint.as_ref()],
+        bump
+    )]
+    pub character: Account<'info, Character>,
+
+    #[account(
+        seeds = [b"staking_authority"],
+        bump
+    )]
+    /// CHECK: PDA that owns staked character NFTs and issues their MOB yield
+    pub staking_authority: UncheckedAccount<'info>,
+
+    /// CHECK: mob_token config, validated by mob_token
+    #[account(mut)]
+    pub mob_config: UncheckedAccount<'info>,
+
+    /// CHECK: mob_token mint authority PDA, validated by mob_token
+    pub mob_mint_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        address = config.mob_mint
+    )]
+    pub mob_mint: Account<'info, Mint>,
+
+    /// CHECK: mob_token player data PDA, validated and created by mob_token
+    #[account(mut)]
+    pub player_data: UncheckedAccount<'info>,
+
+    /// CHECK: Owner's MOB token account, validated and created by mob_token
+    #[account(mut)]
+    pub owner_mob_account: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub mob_token_program: Program<'info, MobToken>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateCollection<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        address = config.collection_mint,
+        mint::decimals = 0,
+        mint::authority = mint_authority,
+    )]
+    pub collection_mint: Account<'info, Mint>,
+
+    #[account(
+        seeds = [b"mint_authority"],
+        bump
+    )]
+    /// CHECK: PDA used as mint authority and collection authority
+    pub mint_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = collection_mint,
+        associated_token::authority = mint_authority,
+    )]
+    pub collection_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Collection metadata account, created by Metaplex
+    #[account(mut)]
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Collection master edition account, created by Metaplex
+    #[account(mut)]
+    pub collection_master_edition: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+
+    /// CHECK: Metaplex token metadata program
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyCharacterCollection<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Only NFTs minted by this program can be verified
+    #[account(
+        seeds = [b"character", character.mint.as_ref()],
+        bump
+    )]
+    pub character: Account<'info, Character>,
+
+    /// CHECK: Character metadata account, derived from the character mint
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), character.mint.as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"mint_authority"],
+        bump
+    )]
+    /// CHECK: PDA used as mint authority and collection authority
+    pub mint_authority: UncheckedAccount<'info>,
+
+    #[account(address = config.collection_mint)]
+    pub collection_mint: Account<'info, Mint>,
+
+    /// CHECK: Collection metadata account, validated by Metaplex
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Collection master edition account, validated by Metaplex
+    pub collection_master_edition: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Metaplex token metadata program
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCharacterMetadata<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [b"character", character.mint.as_ref()],
+        bump
+    )]
+    pub character: Account<'info, Character>,
+
+    /// CHECK: Character metadata account, derived from the character mint
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), character.mint.as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"mint_authority"],
+        bump
+    )]
+    /// CHECK: PDA used as mint authority and metadata update authority
+    pub mint_authority: UncheckedAccount<'info>,
+
     /// CHECK: Metaplex token metadata program
+    #[account(address = mpl_token_metadata::ID)]
     pub token_metadata_program: UncheckedAccount<'info>,
 }
 
-#[derive(Accounts)]
-pub struct LevelUp<'info> {
+#[derive(Accounts)]
+pub struct MergeCharacters<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"character", mint_a.key().as_ref()],
+        bump
+    )]
+    pub character_a: Account<'info, Character>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"character", mint_b.key().as_ref()],
+        bump
+    )]
+    pub character_b: Account<'info, Character>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"character", mint_c.key().as_ref()],
+        bump
+    )]
+    pub character_c: Account<'info, Character>,
+
+    #[account(mut)]
+    pub mint_a: Box<Account<'info, Mint>>,
+
+    #[account(mut)]
+    pub mint_b: Box<Account<'info, Mint>>,
+
+    #[account(mut)]
+    pub mint_c: Box<Account<'info, Mint>>,
+
     #[account(
         mut,
-        seeds = [b"character", character.mint.as_ref()],
+        associated_token::mint = mint_a,
+        associated_token::authority = owner,
+    )]
+    pub token_account_a: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_b,
+        associated_token::authority = owner,
+    )]
+    pub token_account_b: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_c,
+        associated_token::authority = owner,
+    )]
+    pub token_account_c: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = owner,
+        mint::decimals = 0,
+        mint::authority = mint_authority,
+        mint::freeze_authority = mint_authority,
+    )]
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [b"mint_authority"],
+        bump
+    )]
+    /// CHECK: PDA used as mint authority and collection authority
+    pub mint_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint,
+        associated_token::authority = owner,
+    )]
+    pub token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Character::INIT_SPACE,
+        seeds = [b"character", mint.key().as_ref()],
         bump
     )]
     pub character: Account<'info, Character>,
-    
-    pub owner: Signer<'info>,
-}
 
-#[derive(Accounts)]
-pub struct MergeCharacters<'info> {
-    // Accounts for merging characters - to be implemented
+    /// CHECK: Metadata account
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Master edition account
+    #[account(mut)]
+    pub master_edition: UncheckedAccount<'info>,
+
+    /// CHECK: Collection mint, required once the collection is created
+    #[account(address = config.collection_mint)]
+    pub collection_mint: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Collection metadata, required once the collection is created
+    #[account(
+        seeds = [b"metadata", mpl_token_metadata::ID.as_ref(), config.collection_mint.as_ref()],
+        bump,
+        seeds::program = mpl_token_metadata::ID
+    )]
+    pub collection_metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Collection master edition, required once the collection is created
+    #[account(
+        seeds = [
+            b"metadata",
+            mpl_token_metadata::ID.as_ref(),
+            config.collection_mint.as_ref(),
+            b"edition"
+        ],
+        bump,
+        seeds::program = mpl_token_metadata::ID
+    )]
+    pub collection_master_edition: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
     pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+
+    /// CHECK: Metaplex token metadata program
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
 }
 
 #[account]
@@ -317,6 +2044,46 @@ pub struct Config {
     pub max_supply: u32,
     pub mint_price: u64,
     pub is_active: bool,
+    pub total_proceeds: u64,
+    pub rarity_weights: [u16; 6], // Basis points per CharacterRarity, summing to 10000
+    pub game_server: Pubkey, // May grant experience
+    #[max_len(10)]
+    pub xp_sources: Vec<Pubkey>, // Mission and turf program PDAs that may grant experience
+    pub daily_xp_cap: u64, // Per character, per 24 hours
+    pub mob_mint: Pubkey,
+    pub respec_cost: u64, // MOB burned per respec
+    pub staking_yield_rate: u64, // MOB per day for a staked level 1 Common
+    pub collection_created: bool,
+    #[max_len(150)]
+    pub uri_template: String, // e.g. "https://api.example.com/characters/{mint}.json?lv={level}"
+}
+
+impl Config {
+    /// Defaults for every field added after the first release, shared by
+    /// initialize and migrate_config
+    pub fn set_default_extensions(&mut self, authority: Pubkey, mob_mint: Pubkey) {
+        self.total_proceeds = 0;
+        self.rarity_weights = DEFAULT_RARITY_WEIGHTS;
+        self.game_server = authority;
+        self.xp_sources = Vec::new();
+        self.daily_xp_cap = 5_000;
+        self.mob_mint = mob_mint;
+        self.respec_cost = 100_000_000_000; // 100 MOB
+        self.staking_yield_rate = 500_000_000; // 0.5 MOB per day for a level 1 Common
+        self.collection_created = false;
+        self.uri_template = String::new();
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct MintCommitment {
+    pub payer: Pubkey,
+    pub owner: Pubkey,
+    pub commitment: [u8; 32],
+    pub commit_slot: u64,
+    pub price_paid: u64,
+    pub rarity_weights: [u16; 6],
 }
 
 #[account]
@@ -330,9 +2097,62 @@ pub struct Character {
     pub experience: u64,
     pub last_mission_time: i64,
     pub is_staked: bool,
+    pub xp_day_start: i64,
+    pub xp_granted_today: u64,
+    pub allocated_points: [u8; 5], // Points spent per stat since the last respec, in CharacterStats order
+    pub staked_at: i64,
+    pub last_yield_time: i64,
+    pub accrued_yield: u64, // Unclaimed MOB staking yield
+    #[max_len(32)]
+    pub name: String, // Base name, without the level suffix
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+impl Character {
+    /// Stats as rolled, with allocated points removed
+    pub fn base_stats(&self) -> CharacterStats {
+        let mut stats = self.stats.as_array();
+        for (stat, allocated) in stats.iter_mut().zip(self.allocated_points.iter()) {
+            *stat = stat.saturating_sub(*allocated);
+        }
+        let mut base = self.stats;
+        base.set_array(stats);
+        base.available_points = 0;
+        base
+    }
+
+    /// Every stat point earned from levelling: unspent plus allocated
+    pub fn stat_points(&self) -> u16 {
+        self.stats.available_points as u16
+            + self.allocated_points.iter().map(|p| *p as u16).sum::<u16>()
+    }
+
+    /// MOB accrued per day while staked: the base rate scaled by rarity, +5% per level
+    pub fn daily_yield(&self, base_rate: u64) -> u64 {
+        let level_bonus = 100 + 5 * self.level.saturating_sub(1) as u64;
+        base_rate * self.rarity.yield_multiplier() * level_bonus / 100
+    }
+
+    /// Move yield earned since the last checkpoint into `accrued_yield`
+    pub fn accrue_yield(&mut self, base_rate: u64, now: i64) {
+        if self.is_staked && now > self.last_yield_time {
+            let elapsed = (now - self.last_yield_time) as u128;
+            self.accrued_yield += (self.daily_yield(base_rate) as u128 * elapsed / 86400) as u64;
+        }
+        self.last_yield_time = now;
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExperienceReason {
+    Mission,
+    TerritoryCapture,
+    TerritoryDefense,
+    PvpVictory,
+    Achievement,
+    Event,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
 pub enum CharacterRarity {
     Common,
     Uncommon,
@@ -342,6 +2162,22 @@ pub enum CharacterRarity {
     Mythic,
 }
 
+/// Stat points to add, in CharacterStats order
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct StatAllocation {
+    pub strength: u8,
+    pub intelligence: u8,
+    pub charisma: u8,
+    pub luck: u8,
+    pub stealth: u8,
+}
+
+impl StatAllocation {
+    pub fn as_array(&self) -> [u8; 5] {
+        [self.strength, self.intelligence, self.charisma, self.luck, self.stealth]
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
 pub struct CharacterStats {
     pub strength: u8,
@@ -352,18 +2188,124 @@ pub struct CharacterStats {
     pub available_points: u8,
 }
 
-impl CharacterStats {
-    pub fn validate_for_rarity(&self, rarity: &CharacterRarity) -> Result<()> {
-        let total_stats = self.strength + self.intelligence + self.charisma + self.luck + self.stealth;
-        
-        let (min_total, max_total) = match rarity {
+impl CharacterRarity {
+    /// Inclusive range of total base stats allowed for this rarity
+    pub fn stat_total_range(&self) -> (u16, u16) {
+        match self {
             CharacterRarity::Common => (200, 250),
             CharacterRarity::Uncommon => (251, 300),
             CharacterRarity::Rare => (301, 350),
             CharacterRarity::Epic => (351, 400),
             CharacterRarity::Legendary => (401, 450),
             CharacterRarity::Mythic => (451, 500),
+        }
+    }
+
+    /// Pick a rarity from `weights` (basis points per tier) using the seed
+    pub fn roll(seed: &[u8; 32], weights: &[u16; 6]) -> CharacterRarity {
+        let roll = (seed_u64(seed, 0) % RARITY_WEIGHT_TOTAL as u64) as u16;
+        let tiers = [
+            CharacterRarity::Common,
+            CharacterRarity::Uncommon,
+            CharacterRarity::Rare,
+            CharacterRarity::Epic,
+            CharacterRarity::Legendary,
+            CharacterRarity::Mythic,
+        ];
+
+        let mut cumulative = 0u16;
+        for (tier, weight) in tiers.iter().zip(weights.iter()) {
+            cumulative += weight;
+            if roll < cumulative {
+                return *tier;
+            }
+        }
+        CharacterRarity::Common
+    }
+
+    /// Highest value any single stat may be raised to at `level`
+    pub fn stat_cap(&self, level: u8) -> u8 {
+        let base: u8 = match self {
+            CharacterRarity::Common => 120,
+            CharacterRarity::Uncommon => 130,
+            CharacterRarity::Rare => 140,
+            CharacterRarity::Epic => 150,
+            CharacterRarity::Legendary => 160,
+            CharacterRarity::Mythic => 170,
         };
+        base.saturating_add(level)
+    }
+
+    /// Staking yield multiplier relative to Common
+    pub fn yield_multiplier(&self) -> u64 {
+        match self {
+            CharacterRarity::Common => 1,
+            CharacterRarity::Uncommon => 2,
+            CharacterRarity::Rare => 3,
+            CharacterRarity::Epic => 5,
+            CharacterRarity::Legendary => 8,
+            CharacterRarity::Mythic => 12,
+        }
+    }
+
+    /// The tier a merge of this rarity produces, or None for Mythic
+    pub fn next(&self) -> Option<CharacterRarity> {
+        match self {
+            CharacterRarity::Common => Some(CharacterRarity::Uncommon),
+            CharacterRarity::Uncommon => Some(CharacterRarity::Rare),
+            CharacterRarity::Rare => Some(CharacterRarity::Epic),
+            CharacterRarity::Epic => Some(CharacterRarity::Legendary),
+            CharacterRarity::Legendary => Some(CharacterRarity::Mythic),
+            CharacterRarity::Mythic => None,
+        }
+    }
+}
+
+impl CharacterStats {
+    pub fn as_array(&self) -> [u8; 5] {
+        [self.strength, self.intelligence, self.charisma, self.luck, self.stealth]
+    }
+
+    pub fn set_array(&mut self, stats: [u8; 5]) {
+        [self.strength, self.intelligence, self.charisma, self.luck, self.stealth] = stats;
+    }
+
+    pub fn total(&self) -> u16 {
+        self.strength as u16
+            + self.intelligence as u16
+            + self.charisma as u16
+            + self.luck as u16
+            + self.stealth as u16
+    }
+
+    /// Roll a stat total within the rarity's range from the seed, then split
+    /// it across the five stats using seeded weights between 50 and 100
+    pub fn roll(seed: &[u8; 32], rarity: &CharacterRarity) -> CharacterStats {
+        let (min_total, max_total) = rarity.stat_total_range();
+        let total = min_total + (seed_u64(seed, 8) % (max_total - min_total + 1) as u64) as u16;
+
+        let weights: [u16; 5] = core::array::from_fn(|i| 50 + (seed[16 + i] % 51) as u16);
+        let weight_sum: u16 = weights.iter().sum();
+        let mut stats: [u16; 5] = core::array::from_fn(|i| total * weights[i] / weight_sum);
+
+        let remainder = total - stats.iter().sum::<u16>();
+        for (i, stat) in stats.iter_mut().enumerate() {
+            *stat += u16::from((i as u16) < remainder);
+        }
+
+        CharacterStats {
+            strength: stats[0] as u8,
+            intelligence: stats[1] as u8,
+            charisma: stats[2] as u8,
+            luck: stats[3] as u8,
+            stealth: stats[4] as u8,
+            available_points: 0,
+        }
+    }
+
+    pub fn validate_for_rarity(&self, rarity: &CharacterRarity) -> Result<()> {
+        let total_stats = self.total();
+        let (min_total, max_total) = rarity.stat_total_range();
         
         require!(
             total_stats >= min_total && total_stats <= max_total,
@@ -372,6 +2314,405 @@ impl CharacterStats {
         
         Ok(())
     }
+
+    /// Stats for the character produced by merging three characters of
+    /// `rarity`, from their rolled stats: the per-stat average, raised evenly so the total keeps its
+    /// relative position within the next tier's range.
+    pub fn merged(inputs: [&CharacterStats; 3], rarity: &CharacterRarity) -> CharacterStats {
+        let average = |stat: fn(&CharacterStats) -> u8| -> u16 {
+            inputs.iter().map(|s| stat(s) as u16).sum::<u16>() / 3
+        };
+        let mut stats = [
+            average(|s| s.strength),
+            average(|s| s.intelligence),
+            average(|s| s.charisma),
+            average(|s| s.luck),
+            average(|s| s.stealth),
+        ];
+
+        let (min_total, _) = rarity.stat_total_range();
+        let (next_min, next_max) = rarity.next().unwrap_or(*rarity).stat_total_range();
+        let total: u16 = stats.iter().sum();
+        let offset = total.saturating_sub(min_total).min(next_max - next_min);
+        let bonus = (next_min + offset).saturating_sub(total);
+
+        for (i, stat) in stats.iter_mut().enumerate() {
+            *stat += bonus / 5 + u16::from((i as u16) < bonus % 5);
+        }
+
+        CharacterStats {
+            strength: stats[0].min(u8::MAX as u16) as u8,
+            intelligence: stats[1].min(u8::MAX as u16) as u8,
+            charisma: stats[2].min(u8::MAX as u16) as u8,
+            luck: stats[3].min(u8::MAX as u16) as u8,
+            stealth: stats[4].min(u8::MAX as u16) as u8,
+            available_points: 0,
+        }
+    }
+}
+
+/// Accounts needed to mint a character NFT with metadata and master edition
+pub struct CharacterNftAccounts<'info> {
+    pub mint: AccountInfo<'info>,
+    pub mint_authority: AccountInfo<'info>,
+    pub token_account: AccountInfo<'info>,
+    pub metadata: AccountInfo<'info>,
+    pub master_edition: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub rent: AccountInfo<'info>,
+    pub token_metadata_program: AccountInfo<'info>,
+}
+
+/// Collection accounts a character's metadata is verified against
+pub struct CollectionAccounts<'info> {
+    pub mint: AccountInfo<'info>,
+    pub metadata: AccountInfo<'info>,
+    pub master_edition: AccountInfo<'info>,
+}
+
+/// Collection accounts passed with a new character: unused until the
+/// collection is created, required from then on
+fn new_character_collection<'info>(
+    config: &Config,
+    mint: Option<&UncheckedAccount<'info>>,
+    metadata: Option<&UncheckedAccount<'info>>,
+    master_edition: Option<&UncheckedAccount<'info>>,
+) -> Result<Option<CollectionAccounts<'info>>> {
+    if !config.collection_created {
+        return Ok(None);
+    }
+    let (Some(mint), Some(metadata), Some(master_edition)) = (mint, metadata, master_edition) else {
+        return err!(ErrorCode::CollectionAccountsRequired);
+    };
+    Ok(Some(CollectionAccounts {
+        mint: mint.to_account_info(),
+        metadata: metadata.to_account_info(),
+        master_edition: master_edition.to_account_info(),
+    }))
+}
+
+/// Verify a character's metadata in the collection, signing as the mint
+/// authority PDA, which is the collection's update authority
+fn verify_in_collection<'info>(
+    character_mint: Pubkey,
+    metadata: &AccountInfo<'info>,
+    mint_authority: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    collection: &CollectionAccounts<'info>,
+    mint_authority_bump: u8,
+) -> Result<()> {
+    let seeds: &[&[u8]] = &[b"mint_authority", &[mint_authority_bump]];
+    let signer = &[seeds];
+
+    let verify_ix = verify_collection(
+        mpl_token_metadata::ID,
+        metadata.key(),
+        mint_authority.key(),
+        payer.key(),
+        collection.mint.key(),
+        collection.metadata.key(),
+        collection.master_edition.key(),
+        None,
+    );
+
+    anchor_lang::solana_program::program::invoke_signed(
+        &verify_ix,
+        &[
+            metadata.clone(),
+            mint_authority.clone(),
+            payer.clone(),
+            collection.mint.clone(),
+            collection.metadata.clone(),
+            collection.master_edition.clone(),
+        ],
+        signer,
+    )?;
+
+    emit!(CharacterCollectionVerified {
+        mint: character_mint,
+        collection_mint: collection.mint.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Mint the single token of a new NFT to its owner's account, signing as the
+/// mint authority PDA
+fn mint_character_token(accounts: &CharacterNftAccounts, mint_authority_bump: u8) -> Result<()> {
+    let seeds: &[&[u8]] = &[b"mint_authority", &[mint_authority_bump]];
+    let signer = &[seeds];
+
+    let cpi_accounts = MintTo {
+        mint: accounts.mint.clone(),
+        to: accounts.token_account.clone(),
+        authority: accounts.mint_authority.clone(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(accounts.token_program.clone(), cpi_accounts, signer);
+    token::mint_to(cpi_ctx, 1)
+}
+
+/// Create the Metaplex metadata and master edition for a character NFT,
+/// signing as the mint authority PDA. `collection` is the collection mint the
+/// NFT belongs to, or None for the collection NFT itself.
+fn create_character_nft(
+    accounts: CharacterNftAccounts,
+    config: &Config,
+    collection: Option<Pubkey>,
+    name: String,
+    symbol: String,
+    uri: String,
+    mint_authority_bump: u8,
+) -> Result<()> {
+    let seeds: &[&[u8]] = &[b"mint_authority", &[mint_authority_bump]];
+    let signer = &[seeds];
+
+    // Create metadata
+    let creators = vec![
+        Creator {
+            address: config.authority,
+            verified: true,
+            share: 100,
+        }
+    ];
+
+    let collection = collection.map(|key| Collection {
+        verified: false,
+        key,
+    });
+
+    let data = DataV2 {
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points: 500, // 5% royalty
+        creators: Some(creators),
+        collection,
+        uses: None,
+    };
+
+    // Create metadata account
+    let metadata_seeds = &[
+        b"metadata",
+        accounts.token_metadata_program.key.as_ref(),
+        accounts.mint.key.as_ref(),
+    ];
+    let (metadata_pda, _) = Pubkey::find_program_address(
+        metadata_seeds,
+        accounts.token_metadata_program.key,
+    );
+
+    let create_metadata_ix = create_metadata_accounts_v3(
+        accounts.token_metadata_program.key(),
+        metadata_pda,
+        accounts.mint.key(),
+        accounts.mint_authority.key(),
+        accounts.payer.key(),
+        accounts.mint_authority.key(),
+        data,
+        true,
+        true,
+        None,
+    );
+
+    anchor_lang::solana_program::program::invoke_signed(
+        &create_metadata_ix,
+        &[
+            accounts.metadata.clone(),
+            accounts.mint.clone(),
+            accounts.mint_authority.clone(),
+            accounts.payer.clone(),
+            accounts.system_program.clone(),
+            accounts.rent.clone(),
+        ],
+        signer,
+    )?;
+
+    // Create master edition
+    let edition_seeds = &[
+        b"metadata",
+        accounts.token_metadata_program.key.as_ref(),
+        accounts.mint.key.as_ref(),
+        b"edition",
+    ];
+    let (edition_pda, _) = Pubkey::find_program_address(
+        edition_seeds,
+        accounts.token_metadata_program.key,
+    );
+
+    let create_edition_ix = create_master_edition_v3(
+        accounts.token_metadata_program.key(),
+        edition_pda,
+        accounts.mint.key(),
+        accounts.mint_authority.key(),
+        accounts.mint_authority.key(),
+        metadata_pda,
+        accounts.payer.key(),
+        Some(0), // Max supply of 0 means unlimited prints
+    );
+
+    anchor_lang::solana_program::program::invoke_signed(
+        &create_edition_ix,
+        &[
+            accounts.master_edition.clone(),
+            accounts.mint.clone(),
+            accounts.mint_authority.clone(),
+            accounts.payer.clone(),
+            accounts.metadata.clone(),
+            accounts.system_program.clone(),
+            accounts.rent.clone(),
+        ],
+        signer,
+    )?;
+
+    Ok(())
+}
+
+/// Transfer the mint price from the payer into the proceeds vault
+fn collect_mint_price<'info>(
+    payer: AccountInfo<'info>,
+    proceeds_vault: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    price: u64,
+) -> Result<()> {
+    let cpi_accounts = system_program::Transfer {
+        from: payer,
+        to: proceeds_vault,
+    };
+    system_program::transfer(CpiContext::new(system_program, cpi_accounts), price)
+}
+
+/// Base name with a level suffix, e.g. "Vinny (Lv 12)", trimmed to fit the
+/// Metaplex name limit
+fn leveled_name(base: &str, level: u8) -> String {
+    let suffix = format!(" (Lv {})", level);
+    let mut end = base.len().min(MAX_NAME_LENGTH - suffix.len());
+    while !base.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}{}", &base[..end], suffix)
+}
+
+/// Fill the URI template's placeholders from the character's current state
+fn render_metadata_uri(template: &str, character: &Character) -> String {
+    let stats = &character.stats;
+    template
+        .replace("{mint}", &character.mint.to_string())
+        .replace("{level}", &character.level.to_string())
+        .replace("{rarity}", &format!("{:?}", character.rarity).to_lowercase())
+        .replace(
+            "{stats}",
+            &format!(
+                "{}-{}-{}-{}-{}",
+                stats.strength, stats.intelligence, stats.charisma, stats.luck, stats.stealth
+            ),
+        )
+}
+
+/// Rewrite a character's metadata name and URI from its on-chain state,
+/// keeping everything else as it is on-chain, including the collection and
+/// its verification
+fn update_metadata_from_state<'info>(
+    config: &Config,
+    character: &Character,
+    metadata: &AccountInfo<'info>,
+    mint_authority: &AccountInfo<'info>,
+    mint_authority_bump: u8,
+) -> Result<()> {
+    let name = leveled_name(&character.name, character.level);
+    let uri = render_metadata_uri(&config.uri_template, character);
+    require!(uri.len() <= MAX_URI_LENGTH, ErrorCode::UriTooLong);
+
+    let current = Metadata::from_account_info(metadata)?;
+    let data = DataV2 {
+        name: name.clone(),
+        symbol: current.data.symbol.trim_end_matches('\0').to_string(),
+        uri: uri.clone(),
+        seller_fee_basis_points: current.data.seller_fee_basis_points,
+        creators: current.data.creators,
+        collection: current.collection,
+        uses: current.uses,
+    };
+
+    let seeds: &[&[u8]] = &[b"mint_authority", &[mint_authority_bump]];
+    let signer = &[seeds];
+
+    let update_ix = update_metadata_accounts_v2(
+        mpl_token_metadata::ID,
+        metadata.key(),
+        mint_authority.key(),
+        None,
+        Some(data),
+        None,
+        None,
+    );
+
+    anchor_lang::solana_program::program::invoke_signed(
+        &update_ix,
+        &[metadata.clone(), mint_authority.clone()],
+        signer,
+    )?;
+
+    emit!(CharacterMetadataUpdated {
+        mint: character.mint,
+        name,
+        uri,
+        level: character.level,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Keep metadata in step with a level or stat change once a URI template is set
+fn refresh_templated_metadata<'info>(
+    config: &Config,
+    character: &Character,
+    metadata: Option<&UncheckedAccount<'info>>,
+    mint_authority: Option<&UncheckedAccount<'info>>,
+    token_metadata_program: Option<&UncheckedAccount<'info>>,
+    mint_authority_bump: u8,
+) -> Result<()> {
+    if config.uri_template.is_empty() {
+        return Ok(());
+    }
+    let (Some(metadata), Some(mint_authority), Some(_)) =
+        (metadata, mint_authority, token_metadata_program)
+    else {
+        return err!(ErrorCode::MetadataAccountsRequired);
+    };
+    update_metadata_from_state(
+        config,
+        character,
+        &metadata.to_account_info(),
+        &mint_authority.to_account_info(),
+        mint_authority_bump,
+    )
+}
+
+/// Top up rent and grow `account` to `new_len`; new bytes are zeroed
+fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    new_len: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    require!(account.data_len() < new_len, ErrorCode::AlreadyMigrated);
+
+    let rent = Rent::get()?.minimum_balance(new_len);
+    let top_up = rent.saturating_sub(account.lamports());
+    if top_up > 0 {
+        let cpi_accounts = system_program::Transfer {
+            from: payer.clone(),
+            to: account.clone(),
+        };
+        system_program::transfer(CpiContext::new(system_program.clone(), cpi_accounts), top_up)?;
+    }
+
+    account.realloc(new_len, true)?;
+    Ok(())
 }
 
 fn calculate_required_experience(current_level: u8) -> u64 {
@@ -379,6 +2720,163 @@ fn calculate_required_experience(current_level: u8) -> u64 {
     ((current_level as u64).pow(2) * 100) + (current_level as u64 * 50)
 }
 
+#[event]
+pub struct CharacterSold {
+    pub mint: Pubkey,
+    pub buyer: Pubkey,
+    pub owner: Pubkey,
+    pub price: u64,
+    pub total_minted: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MintCommitted {
+    pub payer: Pubkey,
+    pub owner: Pubkey,
+    pub commitment: [u8; 32],
+    pub commit_slot: u64,
+    pub price: u64,
+    pub timestamp: i64,
+}
+
+/// Everything needed to recompute a revealed character's rolls off-chain
+#[event]
+pub struct CharacterRolled {
+    pub mint: Pubkey,
+    pub mint_commitment: Pubkey,
+    pub secret: [u8; 32],
+    pub commit_slot: u64,
+    pub seed_slot: u64,
+    pub slot_hash: [u8; 32],
+    pub seed: [u8; 32],
+    pub rarity_weights: [u16; 6],
+    pub rarity: CharacterRarity,
+    pub stats: CharacterStats,
+}
+
+#[event]
+pub struct CommitmentCancelled {
+    pub payer: Pubkey,
+    pub commit_slot: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProceedsWithdrawn {
+    pub authority: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MintPriceUpdated {
+    pub old_price: u64,
+    pub new_price: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CharactersMerged {
+    pub owner: Pubkey,
+    pub burned_mints: [Pubkey; 3],
+    pub new_mint: Pubkey,
+    pub new_rarity: CharacterRarity,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct XpSourceAdded {
+    pub source: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct XpSourceRemoved {
+    pub source: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ExperienceGranted {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub source: Pubkey,
+    pub amount: u64,
+    pub reason: ExperienceReason,
+    pub total_experience: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StatsAllocated {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub allocation: StatAllocation,
+    pub available_points: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StatsRespecced {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub refunded_points: u16,
+    pub mob_burned: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CharacterStaked {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CharacterUnstaked {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub staked_duration: i64,
+    pub accrued_yield: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CharacterYieldClaimed {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub remaining_yield: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CollectionCreated {
+    pub collection_mint: Pubkey,
+    pub metadata: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CharacterCollectionVerified {
+    pub mint: Pubkey,
+    pub collection_mint: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CharacterMetadataUpdated {
+    pub mint: Pubkey,
+    pub name: String,
+    pub uri: String,
+    pub level: u8,
+    pub timestamp: i64,
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("Minting is currently inactive")]
@@ -399,4 +2897,62 @@ pub enum ErrorCode {
     InsufficientExperience,
     #[msg("Invalid stats for the specified rarity")]
     InvalidStatsForRarity,
+    #[msg("Characters to merge must all have the same rarity")]
+    RarityMismatch,
+    #[msg("Mythic characters cannot be merged any higher")]
+    MaxRarityReached,
+    #[msg("The same character was supplied more than once")]
+    DuplicateMergeInput,
+    #[msg("Unauthorized access")]
+    Unauthorized,
+    #[msg("Invalid amount specified")]
+    InvalidAmount,
+    #[msg("Insufficient proceeds in vault")]
+    InsufficientProceeds,
+    #[msg("Rarity weights must sum to 10000")]
+    InvalidRarityWeights,
+    #[msg("Revealed secret does not match the commitment")]
+    InvalidReveal,
+    #[msg("Reveal must happen after the commit slot")]
+    RevealTooEarly,
+    #[msg("Commit slot hash is no longer available")]
+    RevealExpired,
+    #[msg("Signer is not authorized to grant experience")]
+    UnauthorizedXpSource,
+    #[msg("Experience source is already registered")]
+    XpSourceAlreadyExists,
+    #[msg("Experience source is not registered")]
+    XpSourceNotFound,
+    #[msg("Too many experience sources")]
+    TooManyXpSources,
+    #[msg("Character has reached its daily experience cap")]
+    DailyXpCapReached,
+    #[msg("Not enough available stat points")]
+    InsufficientStatPoints,
+    #[msg("Stat would exceed the cap for this rarity and level")]
+    StatCapExceeded,
+    #[msg("No allocated stat points to refund")]
+    NothingToRespec,
+    #[msg("Spend some available points before respeccing")]
+    TooManyUnspentPoints,
+    #[msg("Character is staked")]
+    CharacterStaked,
+    #[msg("Character is not staked")]
+    CharacterNotStaked,
+    #[msg("No staking yield to claim")]
+    NoYieldToClaim,
+    #[msg("Collection NFT has already been created")]
+    CollectionAlreadyCreated,
+    #[msg("Collection NFT has not been created yet")]
+    CollectionNotCreated,
+    #[msg("Metadata URI template is not configured")]
+    UriTemplateNotSet,
+    #[msg("Spend some available points before merging")]
+    TooManyPointsToMerge,
+    #[msg("Metadata accounts are required while a URI template is set")]
+    MetadataAccountsRequired,
+    #[msg("Account already uses the current layout")]
+    AlreadyMigrated,
+    #[msg("Collection accounts are required once the collection is created")]
+    CollectionAccountsRequired,
 }
@@ -1,11 +1,21 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, Mint, MintTo, Token, TokenAccount, Transfer},
+    token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer},
 };
 
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnW");
 
+const MAX_STAKE_CHECKPOINTS: usize = 32;
+const MAX_DELEGATE_CHECKPOINTS: usize = 32;
+const MIN_LOCK_DURATION: i64 = 604800; // 1 week
+const MAX_LOCK_DURATION: i64 = 63072000; // 2 years
+const MAX_LOCK_BOOST_BPS: u128 = 30000; // +3x voting power at the maximum lock
+const DISTRIBUTION_TYPE_COUNT: usize = 7;
+const REWARD_PRECISION: u128 = 1_000_000_000_000;
+const MAX_UNBONDING_ENTRIES: usize = 5;
+const MAX_VOTING_PERIOD: i64 = 604800; // 1 week
+
 #[program]
 pub mod fam_token {
     use super::*;
@@ -21,6 +31,16 @@ pub mod fam_token {
         config.min_stake_amount = 1_000_000; // 1 FAM minimum stake
         config.voting_period = 259200; // 3 days in seconds
         config.proposal_threshold = 10_000_000; // 10 FAM to create proposal
+        config.quorum_bps = 1000; // 10% of circulating supply must vote
+        config.pass_threshold_bps = 5000; // More than 50% of votes cast must be yes
+        config.execution_delay = 172800; // 48 hour timelock before passed actions run
+        config.distributed = [0; DISTRIBUTION_TYPE_COUNT];
+        config.total_staked = 0;
+        config.reward_rate = 0;
+        config.acc_reward_per_share = 0;
+        config.last_reward_time = Clock::get()?.unix_timestamp;
+        config.unclaimed_rewards = 0;
+        config.unbonding_period = 604800; // 7 days
         
         msg!("FAM governance token initialized");
         Ok(())
@@ -46,8 +66,14 @@ pub mod fam_token {
             ErrorCode::ExceedsMaxSupply
         );
         
-        // Validate distribution amount based on type
-        validate_distribution(amount, &distribution_type, config.total_supply)?;
+        // Team allocations must go through a vesting schedule
+        require!(
+            !matches!(distribution_type, DistributionType::TeamVesting),
+            ErrorCode::VestingRequired
+        );
+        
+        // Validate cumulative distribution against the type's allocation
+        validate_distribution(amount, &distribution_type, config)?;
         
         // Mint tokens
         let seeds = &[
@@ -67,6 +93,7 @@ pub mod fam_token {
         token::mint_to(cpi_ctx, amount)?;
         
         config.circulating_supply += amount;
+        config.distributed[distribution_type as usize] += amount;
         
         emit!(TokensDistributed {
             recipient: ctx.accounts.recipient.key(),
@@ -79,16 +106,209 @@ pub mod fam_token {
         Ok(())
     }
 
-    /// Stake FAM tokens for governance voting power
-    pub fn stake_tokens(ctx: Context<StakeTokens>, amount: u64) -> Result<()> {
+    /// Allocation cap, amount distributed and amount remaining for every distribution type
+    pub fn get_distribution_allocations(
+        ctx: Context<GetDistributionAllocations>,
+    ) -> Result<Vec<DistributionAllocation>> {
         let config = &ctx.accounts.config;
         
+        let allocations = DistributionType::ALL
+            .iter()
+            .map(|distribution_type| {
+                let cap = distribution_cap(distribution_type, config.total_supply);
+                let distributed = config.distributed[*distribution_type as usize];
+                DistributionAllocation {
+                    distribution_type: *distribution_type,
+                    cap,
+                    distributed,
+                    remaining: cap.saturating_sub(distributed),
+                }
+            })
+            .collect();
+        
+        Ok(allocations)
+    }
+
+    /// Mint a team allocation into a vesting vault for a beneficiary (admin only)
+    pub fn create_vesting(
+        ctx: Context<CreateVesting>,
+        index: u64,
+        amount: u64,
+        start_time: i64,
+        cliff_duration: i64,
+        duration: i64,
+        revocable: bool,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        
+        require!(config.is_active, ErrorCode::ProgramInactive);
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(amount > 0, ErrorCode::InvalidVestingSchedule);
+        require!(
+            duration > 0 && cliff_duration >= 0 && cliff_duration <= duration,
+            ErrorCode::InvalidVestingSchedule
+        );
+        let new_circulating = config
+            .circulating_supply
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(
+            new_circulating <= config.total_supply,
+            ErrorCode::ExceedsMaxSupply
+        );
+        let cliff_time = start_time
+            .checked_add(cliff_duration)
+            .ok_or(ErrorCode::MathOverflow)?;
+        validate_distribution(amount, &DistributionType::TeamVesting, config)?;
+        
+        // Mint the full allocation into the vault
+        let seeds: &[&[u8]] = &[b"mint_authority", &[ctx.bumps.mint_authority]];
+        let signer = &[seeds];
+        
+        let cpi_accounts = MintTo {
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.vesting_vault.to_account_info(),
+            authority: ctx.accounts.mint_authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        
+        token::mint_to(cpi_ctx, amount)?;
+        
+        config.circulating_supply = new_circulating;
+        config.distributed[DistributionType::TeamVesting as usize] += amount;
+        
+        let vesting = &mut ctx.accounts.vesting;
+        vesting.beneficiary = ctx.accounts.beneficiary.key();
+        vesting.index = index;
+        vesting.total_amount = amount;
+        vesting.claimed_amount = 0;
+        vesting.start_time = start_time;
+        vesting.cliff_time = cliff_time;
+        vesting.duration = duration;
+        vesting.revocable = revocable;
+        vesting.revoked = false;
+        
+        emit!(VestingCreated {
+            beneficiary: vesting.beneficiary,
+            index,
+            amount,
+            start_time,
+            cliff_time: vesting.cliff_time,
+            duration,
+            revocable,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Created vesting of {} FAM for {}", amount, vesting.beneficiary);
+        Ok(())
+    }
+
+    /// Claim the vested, unclaimed part of a vesting allocation
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+        let vesting = &mut ctx.accounts.vesting;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        let claimable = vesting.vested_amount(current_time) - vesting.claimed_amount;
+        require!(claimable > 0, ErrorCode::NothingToClaim);
+        
+        let seeds: &[&[u8]] = &[b"vesting_authority", &[ctx.bumps.vesting_authority]];
+        let signer = &[seeds];
+        
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vesting_vault.to_account_info(),
+            to: ctx.accounts.beneficiary_token_account.to_account_info(),
+            authority: ctx.accounts.vesting_authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        
+        token::transfer(cpi_ctx, claimable)?;
+        
+        vesting.claimed_amount += claimable;
+        
+        emit!(VestedTokensClaimed {
+            beneficiary: vesting.beneficiary,
+            amount: claimable,
+            total_claimed: vesting.claimed_amount,
+            timestamp: current_time,
+        });
+        
+        msg!("Claimed {} vested FAM", claimable);
+        Ok(())
+    }
+
+    /// Stop a revocable vesting and burn its unvested tokens, returning them
+    /// to the team allocation (admin only)
+    pub fn revoke_vesting(ctx: Context<RevokeVesting>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let vesting = &mut ctx.accounts.vesting;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(vesting.revocable, ErrorCode::VestingNotRevocable);
+        require!(!vesting.revoked, ErrorCode::VestingAlreadyRevoked);
+        
+        // Whatever has vested so far stays claimable by the beneficiary
+        let vested = vesting.vested_amount(current_time);
+        let unvested = vesting.total_amount - vested;
+        
+        if unvested > 0 {
+            let seeds: &[&[u8]] = &[b"vesting_authority", &[ctx.bumps.vesting_authority]];
+            let signer = &[seeds];
+            
+            let cpi_accounts = Burn {
+                mint: ctx.accounts.mint.to_account_info(),
+                from: ctx.accounts.vesting_vault.to_account_info(),
+                authority: ctx.accounts.vesting_authority.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            
+            token::burn(cpi_ctx, unvested)?;
+        }
+        
+        config.circulating_supply -= unvested;
+        config.distributed[DistributionType::TeamVesting as usize] -= unvested;
+        vesting.total_amount = vested;
+        vesting.revoked = true;
+        
+        emit!(VestingRevoked {
+            beneficiary: vesting.beneficiary,
+            vested_amount: vested,
+            burned_amount: unvested,
+            timestamp: current_time,
+        });
+        
+        msg!("Revoked vesting, burned {} unvested FAM", unvested);
+        Ok(())
+    }
+
+    /// Stake FAM tokens for governance voting power, locked for `lock_duration` seconds
+    pub fn stake_tokens(ctx: Context<StakeTokens>, amount: u64, lock_duration: i64) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        
         require!(config.is_active, ErrorCode::ProgramInactive);
         require!(amount >= config.min_stake_amount, ErrorCode::InsufficientStakeAmount);
+        require!(
+            (MIN_LOCK_DURATION..=MAX_LOCK_DURATION).contains(&lock_duration),
+            ErrorCode::InvalidLockDuration
+        );
         
         let stake_account = &mut ctx.accounts.stake_account;
+        require!(stake_account.delegate.is_none(), ErrorCode::DelegationActive);
         let current_time = Clock::get()?.unix_timestamp;
         
+        // Settle rewards earned on the old balance before it changes
+        config.update_rewards(ctx.accounts.reward_vault.amount, current_time);
+        stake_account.settle_rewards(config);
+        
         // Transfer tokens to stake account
         let cpi_accounts = Transfer {
             from: ctx.accounts.user_token_account.to_account_info(),
@@ -103,14 +323,24 @@ pub mod fam_token {
         // Update stake account
         stake_account.user = ctx.accounts.user.key();
         stake_account.staked_amount += amount;
+        stake_account.reset_reward_debt(config);
+        config.total_staked += amount;
         stake_account.last_stake_time = current_time;
-        stake_account.voting_power = calculate_voting_power(stake_account.staked_amount, current_time);
+        // Adding stake can extend the lock but never shorten it
+        stake_account.lock_end = stake_account.lock_end.max(current_time + lock_duration);
+        stake_account.voting_power = calculate_voting_power(
+            stake_account.staked_amount,
+            stake_account.lock_end,
+            current_time,
+        );
+        stake_account.record_checkpoint(current_time)?;
         
         emit!(TokensStaked {
             user: ctx.accounts.user.key(),
             amount,
             total_staked: stake_account.staked_amount,
             voting_power: stake_account.voting_power,
+            lock_end: stake_account.lock_end,
             timestamp: current_time,
         });
         
@@ -118,9 +348,9 @@ pub mod fam_token {
         Ok(())
     }
 
-    /// Unstake FAM tokens (with cooldown period)
-    pub fn unstake_tokens(ctx: Context<UnstakeTokens>, amount: u64) -> Result<()> {
-        let config = &ctx.accounts.config;
+    /// Start unbonding part of an unlocked stake; it stops counting immediately
+    pub fn request_unstake(ctx: Context<RequestUnstake>, amount: u64) -> Result<()> {
+        let config = &mut ctx.accounts.config;
         let stake_account = &mut ctx.accounts.stake_account;
         let current_time = Clock::get()?.unix_timestamp;
         
@@ -129,21 +359,71 @@ pub mod fam_token {
             ctx.accounts.user.key() == stake_account.user,
             ErrorCode::Unauthorized
         );
+        require!(amount > 0, ErrorCode::InsufficientStakeAmount);
         require!(amount <= stake_account.staked_amount, ErrorCode::InsufficientStakedTokens);
+        require!(stake_account.delegate.is_none(), ErrorCode::DelegationActive);
+        require!(current_time >= stake_account.lock_end, ErrorCode::StakeLocked);
+        require!(
+            stake_account.unbonding.len() < MAX_UNBONDING_ENTRIES,
+            ErrorCode::TooManyUnbondingEntries
+        );
+        
+        // Settle rewards earned on the old balance before it changes
+        config.update_rewards(ctx.accounts.reward_vault.amount, current_time);
+        stake_account.settle_rewards(config);
+        
+        let release_time = current_time + config.unbonding_period;
+        stake_account.staked_amount -= amount;
+        stake_account.reset_reward_debt(config);
+        config.total_staked -= amount;
+        stake_account.voting_power = calculate_voting_power(
+            stake_account.staked_amount,
+            stake_account.lock_end,
+            current_time,
+        );
+        stake_account.record_checkpoint(current_time)?;
+        stake_account.unbonding.push(UnbondingEntry {
+            amount,
+            release_time,
+        });
+        
+        emit!(UnstakeRequested {
+            user: ctx.accounts.user.key(),
+            amount,
+            release_time,
+            remaining_staked: stake_account.staked_amount,
+            voting_power: stake_account.voting_power,
+            timestamp: current_time,
+        });
+        
+        msg!("Unbonding {} FAM tokens until {}", amount, release_time);
+        Ok(())
+    }
+
+    /// Pay out every unbonding entry whose release time has passed
+    pub fn withdraw_unbonded(ctx: Context<WithdrawUnbonded>) -> Result<()> {
+        let stake_account = &mut ctx.accounts.stake_account;
+        let current_time = Clock::get()?.unix_timestamp;
         
-        // Check cooldown period (7 days)
-        let cooldown_period = 604800; // 7 days in seconds
         require!(
-            current_time - stake_account.last_stake_time >= cooldown_period,
-            ErrorCode::CooldownPeriodActive
+            ctx.accounts.user.key() == stake_account.user,
+            ErrorCode::Unauthorized
         );
         
+        let amount: u64 = stake_account
+            .unbonding
+            .iter()
+            .filter(|entry| entry.release_time <= current_time)
+            .map(|entry| entry.amount)
+            .sum();
+        require!(amount > 0, ErrorCode::UnbondingPeriodActive);
+        stake_account
+            .unbonding
+            .retain(|entry| entry.release_time > current_time);
+        
         // Transfer tokens back to user
-        let seeds = &[
-            b"stake_authority",
-            &[ctx.bumps.stake_authority],
-        ];
-        let signer = &[&seeds[..]];
+        let seeds: &[&[u8]] = &[b"stake_authority", &[ctx.bumps.stake_authority]];
+        let signer = &[seeds];
         
         let cpi_accounts = Transfer {
             from: ctx.accounts.stake_token_account.to_account_info(),
@@ -155,10 +435,6 @@ pub mod fam_token {
         
         token::transfer(cpi_ctx, amount)?;
         
-        // Update stake account
-        stake_account.staked_amount -= amount;
-        stake_account.voting_power = calculate_voting_power(stake_account.staked_amount, current_time);
-        
         emit!(TokensUnstaked {
             user: ctx.accounts.user.key(),
             amount,
@@ -167,7 +443,111 @@ pub mod fam_token {
             timestamp: current_time,
         });
         
-        msg!("Unstaked {} FAM tokens", amount);
+        msg!("Withdrew {} unbonded FAM tokens", amount);
+        Ok(())
+    }
+
+    /// Update program configuration (admin only)
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        new_unbonding_period: Option<i64>,
+        new_is_active: Option<bool>,
+        new_voting_period: Option<i64>,
+        new_execution_delay: Option<i64>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        
+        if let Some(period) = new_unbonding_period {
+            require!(period >= 0, ErrorCode::InvalidUnbondingPeriod);
+            config.unbonding_period = period;
+        }
+        
+        if let Some(active) = new_is_active {
+            config.is_active = active;
+        }
+        
+        // Applies to proposals created afterwards
+        if let Some(period) = new_voting_period {
+            require!(
+                period > 0 && period <= MAX_VOTING_PERIOD,
+                ErrorCode::InvalidGovernancePeriod
+            );
+            config.voting_period = period;
+        }
+        
+        if let Some(delay) = new_execution_delay {
+            require!(delay >= 0, ErrorCode::InvalidGovernancePeriod);
+            config.execution_delay = delay;
+        }
+        
+        msg!("FAM token config updated");
+        Ok(())
+    }
+
+    /// Claim FAM staking rewards accrued so far
+    pub fn claim_staking_rewards(ctx: Context<ClaimStakingRewards>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let stake_account = &mut ctx.accounts.stake_account;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        config.update_rewards(ctx.accounts.reward_vault.amount, current_time);
+        stake_account.settle_rewards(config);
+        stake_account.reset_reward_debt(config);
+        
+        let amount = stake_account.pending_rewards;
+        require!(amount > 0, ErrorCode::NothingToClaim);
+        
+        let seeds: &[&[u8]] = &[b"stake_authority", &[ctx.bumps.stake_authority]];
+        let signer = &[seeds];
+        
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.reward_vault.to_account_info(),
+            to: ctx.accounts.user_token_account.to_account_info(),
+            authority: ctx.accounts.stake_authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        
+        token::transfer(cpi_ctx, amount)?;
+        
+        stake_account.pending_rewards = 0;
+        config.unclaimed_rewards -= amount;
+        
+        emit!(StakingRewardsClaimed {
+            user: ctx.accounts.user.key(),
+            amount,
+            timestamp: current_time,
+        });
+        
+        msg!("Claimed {} FAM staking rewards", amount);
+        Ok(())
+    }
+
+    /// Set the FAM per second streamed to stakers from the reward vault (admin only)
+    pub fn set_reward_rate(ctx: Context<SetRewardRate>, reward_rate: u64) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        
+        // Accrue at the old rate up to now before switching
+        config.update_rewards(ctx.accounts.reward_vault.amount, current_time);
+        config.reward_rate = reward_rate;
+        
+        emit!(RewardRateUpdated {
+            reward_rate,
+            timestamp: current_time,
+        });
+        
+        msg!("Staking reward rate set to {} per second", reward_rate);
         Ok(())
     }
 
@@ -177,6 +557,7 @@ pub mod fam_token {
         title: String,
         description: String,
         proposal_type: ProposalType,
+        action: Option<GovernanceAction>,
     ) -> Result<()> {
         let config = &ctx.accounts.config;
         let stake_account = &ctx.accounts.stake_account;
@@ -188,6 +569,10 @@ pub mod fam_token {
             stake_account.staked_amount >= config.proposal_threshold,
             ErrorCode::InsufficientStakeForProposal
         );
+        require!(
+            action.is_none() || matches!(proposal_type, ProposalType::EconomicParameter),
+            ErrorCode::ActionNotAllowed
+        );
         
         let proposal = &mut ctx.accounts.proposal;
         let current_time = Clock::get()?.unix_timestamp;
@@ -201,6 +586,7 @@ pub mod fam_token {
         proposal.yes_votes = 0;
         proposal.no_votes = 0;
         proposal.status = ProposalStatus::Active;
+        proposal.action = action;
         
         emit!(ProposalCreated {
             proposal_id: proposal.key(),
@@ -220,7 +606,6 @@ pub mod fam_token {
         vote: Vote,
     ) -> Result<()> {
         let proposal = &mut ctx.accounts.proposal;
-        let stake_account = &ctx.accounts.stake_account;
         let vote_record = &mut ctx.accounts.vote_record;
         let current_time = Clock::get()?.unix_timestamp;
         
@@ -232,16 +617,35 @@ pub mod fam_token {
             current_time <= proposal.voting_ends_at,
             ErrorCode::VotingPeriodEnded
         );
-        require!(
-            stake_account.staked_amount > 0,
-            ErrorCode::NoVotingPower
-        );
         require!(
             vote_record.voter == Pubkey::default(), // First time voting
             ErrorCode::AlreadyVoted
         );
         
-        let voting_power = stake_account.voting_power;
+        // Only stake held before the proposal was created counts, so tokens
+        // can't be unstaked, moved and restaked to vote twice. Stake that was
+        // delegated at that point is voted by the delegate, never by its owner.
+        let own_power = ctx
+            .accounts
+            .stake_account
+            .as_ref()
+            .and_then(|stake_account| stake_account.checkpoint_before(proposal.created_at))
+            .filter(|checkpoint| !checkpoint.is_delegated)
+            .map_or(0, |checkpoint| {
+                calculate_voting_power(
+                    checkpoint.staked_amount,
+                    checkpoint.lock_end,
+                    proposal.created_at,
+                )
+            });
+        let delegated_power = ctx
+            .accounts
+            .delegate_power
+            .as_ref()
+            .and_then(|delegate_power| delegate_power.checkpoint_before(proposal.created_at))
+            .map_or(0, |checkpoint| checkpoint.delegated_amount);
+        let voting_power = own_power + delegated_power;
+        require!(voting_power > 0, ErrorCode::NoVotingPower);
         
         // Record vote
         vote_record.voter = ctx.accounts.voter.key();
@@ -267,139 +671,781 @@ pub mod fam_token {
         msg!("Vote cast: {:?} with power {}", vote, voting_power);
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + TokenConfig::INIT_SPACE,
-        seeds = [b"config"],
-        bump
-    )]
-    pub config: Account<'info, TokenConfig>,
-    
-    #[account(
-        seeds = [b"mint_authority"],
-        bump
-    )]
-    /// CHECK: PDA used as mint authority
-    pub mint_authority: UncheckedAccount<'info>,
-    
-    #[account(
-        init,
-        payer = authority,
-        mint::decimals = 6,
-        mint::authority = mint_authority,
-    )]
-    pub mint: Account<'info, Mint>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
-}
+    /// Open an account for receiving delegated voting power
+    pub fn register_delegate(ctx: Context<RegisterDelegate>) -> Result<()> {
+        let delegate_power = &mut ctx.accounts.delegate_power;
+        delegate_power.delegate = ctx.accounts.delegate.key();
+        delegate_power.delegated_amount = 0;
+        delegate_power.delegator_count = 0;
+        delegate_power.checkpoints = Vec::new();
+        
+        emit!(DelegateRegistered {
+            delegate: delegate_power.delegate,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Registered delegate {}", delegate_power.delegate);
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct DistributeTokens<'info> {
-    #[account(
-        mut,
-        seeds = [b"config"],
-        bump
-    )]
-    pub config: Account<'info, TokenConfig>,
-    
-    #[account(
-        seeds = [b"mint_authority"],
-        bump
-    )]
-    /// CHECK: PDA used as mint authority
-    pub mint_authority: UncheckedAccount<'info>,
-    
-    #[account(mut)]
-    pub mint: Account<'info, Mint>,
-    
-    #[account(
-        init_if_needed,
-        payer = payer,
-        associated_token::mint = mint,
-        associated_token::authority = recipient,
-    )]
-    pub recipient_token_account: Account<'info, TokenAccount>,
-    
-    /// CHECK: Recipient of tokens
-    pub recipient: UncheckedAccount<'info>,
-    
-    pub authority: Signer<'info>,
-    
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub system_program: Program<'info, System>,
+    /// Delegate this stake's voting power to a registered delegate.
+    /// Delegated stake votes at 1x: the lock boost decays on each stake's own
+    /// schedule and can't be summed into one total, so it only applies when
+    /// the owner votes directly.
+    pub fn delegate_votes(ctx: Context<DelegateVotes>) -> Result<()> {
+        let stake_account = &mut ctx.accounts.stake_account;
+        let delegate_power = &mut ctx.accounts.delegate_power;
+        let delegate = delegate_power.delegate;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        require!(
+            ctx.accounts.user.key() == stake_account.user,
+            ErrorCode::Unauthorized
+        );
+        require!(delegate != stake_account.user, ErrorCode::CannotDelegateToSelf);
+        require!(stake_account.delegate.is_none(), ErrorCode::DelegationActive);
+        require!(stake_account.staked_amount > 0, ErrorCode::InsufficientStakedTokens);
+        
+        stake_account.delegate = Some(delegate);
+        delegate_power.delegated_amount += stake_account.staked_amount;
+        delegate_power.delegator_count += 1;
+        stake_account.record_checkpoint(current_time)?;
+        delegate_power.record_checkpoint(current_time)?;
+        // Keep a free entry for every active delegator so revoking never fails
+        require!(
+            delegate_power.checkpoints.len() + delegate_power.delegator_count as usize
+                <= MAX_DELEGATE_CHECKPOINTS,
+            ErrorCode::CheckpointHistoryFull
+        );
+        
+        emit!(VotesDelegated {
+            delegator: stake_account.user,
+            delegate,
+            amount: stake_account.staked_amount,
+            delegate_total: delegate_power.delegated_amount,
+            timestamp: current_time,
+        });
+        
+        msg!("Delegated {} FAM of voting power to {}", stake_account.staked_amount, delegate);
+        Ok(())
+    }
+
+    /// Take back voting power previously delegated
+    pub fn revoke_delegation(ctx: Context<RevokeDelegation>) -> Result<()> {
+        let stake_account = &mut ctx.accounts.stake_account;
+        let delegate_power = &mut ctx.accounts.delegate_power;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        require!(
+            ctx.accounts.user.key() == stake_account.user,
+            ErrorCode::Unauthorized
+        );
+        let delegate = stake_account.delegate.ok_or(ErrorCode::NoActiveDelegation)?;
+        
+        stake_account.delegate = None;
+        delegate_power.delegated_amount -= stake_account.staked_amount;
+        delegate_power.delegator_count -= 1;
+        stake_account.record_checkpoint(current_time)?;
+        delegate_power.record_checkpoint(current_time)?;
+        
+        emit!(DelegationRevoked {
+            delegator: stake_account.user,
+            delegate,
+            amount: stake_account.staked_amount,
+            delegate_total: delegate_power.delegated_amount,
+            timestamp: current_time,
+        });
+        
+        msg!("Revoked delegation to {}", delegate);
+        Ok(())
+    }
+
+    /// Close voting and apply quorum and pass thresholds (callable by anyone)
+    pub fn finalize_proposal(ctx: Context<FinalizeProposal>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let proposal = &mut ctx.accounts.proposal;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        require!(
+            matches!(proposal.status, ProposalStatus::Active),
+            ErrorCode::ProposalNotActive
+        );
+        require!(
+            current_time > proposal.voting_ends_at,
+            ErrorCode::VotingPeriodActive
+        );
+        
+        let total_votes = proposal.yes_votes as u128 + proposal.no_votes as u128;
+        let quorum = config.circulating_supply as u128 * config.quorum_bps as u128 / 10000;
+        let quorum_reached = total_votes > 0 && total_votes >= quorum;
+        let passed = quorum_reached
+            && proposal.yes_votes as u128 * 10000 > total_votes * config.pass_threshold_bps as u128;
+        
+        proposal.status = if passed {
+            ProposalStatus::Passed
+        } else {
+            ProposalStatus::Rejected
+        };
+        
+        emit!(ProposalFinalized {
+            proposal_id: proposal.key(),
+            status: proposal.status,
+            yes_votes: proposal.yes_votes,
+            no_votes: proposal.no_votes,
+            quorum_reached,
+            timestamp: current_time,
+        });
+        
+        msg!("Proposal finalized: {:?}", proposal.status);
+        Ok(())
+    }
+
+    /// Cancel a proposal that has not been executed (proposer or authority)
+    pub fn cancel_proposal(ctx: Context<CancelProposal>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let proposal = &mut ctx.accounts.proposal;
+        let signer = ctx.accounts.signer.key();
+        
+        require!(
+            signer == proposal.proposer || signer == config.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            matches!(proposal.status, ProposalStatus::Active | ProposalStatus::Passed),
+            ErrorCode::ProposalNotCancellable
+        );
+        
+        proposal.status = ProposalStatus::Cancelled;
+        
+        emit!(ProposalCancelled {
+            proposal_id: proposal.key(),
+            cancelled_by: signer,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Proposal cancelled: {}", proposal.title);
+        Ok(())
+    }
+
+    /// Apply a passed proposal's action once the timelock has elapsed (callable by anyone)
+    pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let proposal = &ctx.accounts.proposal;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        require!(
+            matches!(proposal.status, ProposalStatus::Passed),
+            ErrorCode::ProposalNotPassed
+        );
+        require!(
+            current_time >= proposal.voting_ends_at + config.execution_delay,
+            ErrorCode::TimelockActive
+        );
+        let action = proposal.action.ok_or(ErrorCode::NoExecutableAction)?;
+        require_keys_eq!(
+            ctx.accounts.target_program.key(),
+            action.target_program(),
+            ErrorCode::InvalidTargetProgram
+        );
+        
+        let seeds: &[&[u8]] = &[b"governance", &[ctx.bumps.governance]];
+        let signer = &[seeds];
+        let target_program = ctx.accounts.target_program.to_account_info();
+        let target_config = ctx.accounts.target_config.to_account_info();
+        let governance = ctx.accounts.governance.to_account_info();
+        
+        match action {
+            GovernanceAction::MarketplaceFeeRate(rate) => {
+                let cpi_accounts = game_treasury::cpi::accounts::UpdateConfig {
+                    treasury: target_config,
+                    authority: governance,
+                };
+                let cpi_ctx = CpiContext::new_with_signer(target_program, cpi_accounts, signer);
+                game_treasury::cpi::update_config(cpi_ctx, Some(rate), None, None, None, None)?;
+            }
+            GovernanceAction::TerritoryTaxRate(rate) => {
+                let cpi_accounts = game_treasury::cpi::accounts::UpdateConfig {
+                    treasury: target_config,
+                    authority: governance,
+                };
+                let cpi_ctx = CpiContext::new_with_signer(target_program, cpi_accounts, signer);
+                game_treasury::cpi::update_config(cpi_ctx, None, Some(rate), None, None, None)?;
+            }
+            GovernanceAction::StakingRewardVault(vault) => {
+                let cpi_accounts = game_treasury::cpi::accounts::UpdateConfig {
+                    treasury: target_config,
+                    authority: governance,
+                };
+                let cpi_ctx = CpiContext::new_with_signer(target_program, cpi_accounts, signer);
+                game_treasury::cpi::update_config(cpi_ctx, None, None, None, None, Some(vault))?;
+            }
+            GovernanceAction::DailyEmission(emission) => {
+                let cpi_accounts = mob_token::cpi::accounts::UpdateConfig {
+                    config: target_config,
+                    authority: governance,
+                };
+                let cpi_ctx = CpiContext::new_with_signer(target_program, cpi_accounts, signer);
+                mob_token::cpi::update_config(cpi_ctx, Some(emission), None, None, None, None)?;
+            }
+            GovernanceAction::AttackCooldown(cooldown) => {
+                let cpi_accounts = turf_control::cpi::accounts::UpdateConfig {
+                    config: target_config,
+                    authority: governance,
+                };
+                let cpi_ctx = CpiContext::new_with_signer(target_program, cpi_accounts, signer);
+                turf_control::cpi::update_config(cpi_ctx, Some(cooldown), None, None, None, None, None)?;
+            }
+        }
+        
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.status = ProposalStatus::Executed;
+        
+        emit!(ProposalExecuted {
+            proposal_id: proposal.key(),
+            executed_by: ctx.accounts.executor.key(),
+            timestamp: current_time,
+        });
+        
+        msg!("Proposal action executed: {:?}", action);
+        Ok(())
+    }
+
+    /// Record that a passed proposal has been carried out (admin only)
+    pub fn mark_executed(ctx: Context<MarkExecuted>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let proposal = &mut ctx.accounts.proposal;
+        
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            matches!(proposal.status, ProposalStatus::Passed),
+            ErrorCode::ProposalNotPassed
+        );
+        require!(proposal.action.is_none(), ErrorCode::ExecutableActionPending);
+        
+        proposal.status = ProposalStatus::Executed;
+        
+        emit!(ProposalExecuted {
+            proposal_id: proposal.key(),
+            executed_by: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Proposal executed: {}", proposal.title);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + TokenConfig::INIT_SPACE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"mint_authority"],
+        bump
+    )]
+    /// CHECK: PDA used as mint authority
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = 6,
+        mint::authority = mint_authority,
+    )]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = mint,
+        token::authority = stake_authority,
+        seeds = [b"reward_vault"],
+        bump
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"stake_authority"],
+        bump
+    )]
+    /// CHECK: PDA used as stake authority
+    pub stake_authority: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct DistributeTokens<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"mint_authority"],
+        bump
+    )]
+    /// CHECK: PDA used as mint authority
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = recipient,
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Recipient of tokens
+    pub recipient: UncheckedAccount<'info>,
+    
+    pub authority: Signer<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GetDistributionAllocations<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct CreateVesting<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"mint_authority"],
+        bump
+    )]
+    /// CHECK: PDA used as mint authority
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(mut, address = config.mint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + VestingAccount::INIT_SPACE,
+        seeds = [b"vesting", beneficiary.key().as_ref(), &index.to_le_bytes()],
+        bump
+    )]
+    pub vesting: Account<'info, VestingAccount>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = mint,
+        token::authority = vesting_authority,
+        seeds = [b"vesting_vault", beneficiary.key().as_ref(), &index.to_le_bytes()],
+        bump
+    )]
+    pub vesting_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"vesting_authority"],
+        bump
+    )]
+    /// CHECK: PDA that owns vesting vaults
+    pub vesting_authority: UncheckedAccount<'info>,
+    
+    /// CHECK: Beneficiary of the vesting schedule
+    pub beneficiary: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(
+        mut,
+        seeds = [b"vesting", beneficiary.key().as_ref(), &vesting.index.to_le_bytes()],
+        bump,
+        has_one = beneficiary
+    )]
+    pub vesting: Account<'info, VestingAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"vesting_vault", beneficiary.key().as_ref(), &vesting.index.to_le_bytes()],
+        bump
+    )]
+    pub vesting_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"vesting_authority"],
+        bump
+    )]
+    /// CHECK: PDA that owns vesting vaults
+    pub vesting_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = beneficiary,
+        associated_token::mint = mint,
+        associated_token::authority = beneficiary,
+    )]
+    pub beneficiary_token_account: Account<'info, TokenAccount>,
+    
+    #[account(address = vesting_vault.mint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeVesting<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"vesting", vesting.beneficiary.as_ref(), &vesting.index.to_le_bytes()],
+        bump
+    )]
+    pub vesting: Account<'info, VestingAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"vesting_vault", vesting.beneficiary.as_ref(), &vesting.index.to_le_bytes()],
+        bump
+    )]
+    pub vesting_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"vesting_authority"],
+        bump
+    )]
+    /// CHECK: PDA that owns vesting vaults
+    pub vesting_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = config.mint
+    )]
+    pub mint: Account<'info, Mint>,
+    
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
 pub struct StakeTokens<'info> {
     #[account(
+        mut,
         seeds = [b"config"],
         bump
     )]
     pub config: Account<'info, TokenConfig>,
     
     #[account(
-        init_if_needed,
-        payer = user,
-        space = 8 + StakeAccount::INIT_SPACE,
-        seeds = [b"stake", user.key().as_ref()],
+        seeds = [b"reward_vault"],
+        bump
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + StakeAccount::INIT_SPACE,
+        seeds = [b"stake", user.key().as_ref()],
+        bump
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+    
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint,
+        associated_token::authority = stake_authority,
+    )]
+    pub stake_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"stake_authority"],
+        bump
+    )]
+    /// CHECK: PDA used as stake authority
+    pub stake_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = user,
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RequestUnstake<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"reward_vault"],
+        bump
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"stake", user.key().as_ref()],
+        bump
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+    
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawUnbonded<'info> {
+    #[account(
+        mut,
+        seeds = [b"stake", user.key().as_ref()],
+        bump
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+    
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = stake_authority,
+    )]
+    pub stake_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"stake_authority"],
+        bump
+    )]
+    /// CHECK: PDA used as stake authority
+    pub stake_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = user,
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    pub mint: Account<'info, Mint>,
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimStakingRewards<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"stake", user.key().as_ref()],
+        bump
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward_vault"],
+        bump
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"stake_authority"],
+        bump
+    )]
+    /// CHECK: PDA used as stake authority
+    pub stake_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        associated_token::mint = config.mint,
+        associated_token::authority = user,
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetRewardRate<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"reward_vault"],
+        bump
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"stake", proposer.key().as_ref()],
+        bump
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+    
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + Proposal::INIT_SPACE,
+        seeds = [b"proposal", proposer.key().as_ref(), &Clock::get()?.unix_timestamp.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VoteOnProposal<'info> {
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+    
+    #[account(
+        seeds = [b"stake", voter.key().as_ref()],
         bump
     )]
-    pub stake_account: Account<'info, StakeAccount>,
-    
-    #[account(
-        init_if_needed,
-        payer = user,
-        associated_token::mint = mint,
-        associated_token::authority = stake_authority,
-    )]
-    pub stake_token_account: Account<'info, TokenAccount>,
+    pub stake_account: Option<Account<'info, StakeAccount>>,
     
     #[account(
-        seeds = [b"stake_authority"],
+        seeds = [b"delegate_power", voter.key().as_ref()],
         bump
     )]
-    /// CHECK: PDA used as stake authority
-    pub stake_authority: UncheckedAccount<'info>,
+    pub delegate_power: Option<Account<'info, DelegatePower>>,
     
     #[account(
-        mut,
-        associated_token::mint = mint,
-        associated_token::authority = user,
+        init,
+        payer = voter,
+        space = 8 + VoteRecord::INIT_SPACE,
+        seeds = [b"vote", proposal.key().as_ref(), voter.key().as_ref()],
+        bump
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
-    
-    pub mint: Account<'info, Mint>,
+    pub vote_record: Account<'info, VoteRecord>,
     
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub voter: Signer<'info>,
     
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct UnstakeTokens<'info> {
+pub struct RegisterDelegate<'info> {
     #[account(
-        seeds = [b"config"],
+        init,
+        payer = delegate,
+        space = 8 + DelegatePower::INIT_SPACE,
+        seeds = [b"delegate_power", delegate.key().as_ref()],
         bump
     )]
-    pub config: Account<'info, TokenConfig>,
+    pub delegate_power: Account<'info, DelegatePower>,
+    
+    #[account(mut)]
+    pub delegate: Signer<'info>,
     
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DelegateVotes<'info> {
     #[account(
         mut,
         seeds = [b"stake", user.key().as_ref()],
@@ -409,83 +1455,100 @@ pub struct UnstakeTokens<'info> {
     
     #[account(
         mut,
-        associated_token::mint = mint,
-        associated_token::authority = stake_authority,
+        seeds = [b"delegate_power", delegate_power.delegate.as_ref()],
+        bump
     )]
-    pub stake_token_account: Account<'info, TokenAccount>,
+    pub delegate_power: Account<'info, DelegatePower>,
     
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeDelegation<'info> {
     #[account(
-        seeds = [b"stake_authority"],
+        mut,
+        seeds = [b"stake", user.key().as_ref()],
         bump
     )]
-    /// CHECK: PDA used as stake authority
-    pub stake_authority: UncheckedAccount<'info>,
+    pub stake_account: Account<'info, StakeAccount>,
     
     #[account(
         mut,
-        associated_token::mint = mint,
-        associated_token::authority = user,
+        seeds = [b"delegate_power", delegate_power.delegate.as_ref()],
+        bump,
+        constraint = Some(delegate_power.delegate) == stake_account.delegate @ ErrorCode::NoActiveDelegation
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub delegate_power: Account<'info, DelegatePower>,
     
-    pub mint: Account<'info, Mint>,
     pub user: Signer<'info>,
-    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct CreateProposal<'info> {
+pub struct FinalizeProposal<'info> {
     #[account(
         seeds = [b"config"],
         bump
     )]
     pub config: Account<'info, TokenConfig>,
     
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct CancelProposal<'info> {
     #[account(
-        seeds = [b"stake", proposer.key().as_ref()],
+        seeds = [b"config"],
         bump
     )]
-    pub stake_account: Account<'info, StakeAccount>,
+    pub config: Account<'info, TokenConfig>,
     
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+    
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MarkExecuted<'info> {
     #[account(
-        init,
-        payer = proposer,
-        space = 8 + Proposal::INIT_SPACE,
-        seeds = [b"proposal", proposer.key().as_ref(), &Clock::get()?.unix_timestamp.to_le_bytes()],
+        seeds = [b"config"],
         bump
     )]
-    pub proposal: Account<'info, Proposal>,
+    pub config: Account<'info, TokenConfig>,
     
     #[account(mut)]
-    pub proposer: Signer<'info>,
+    pub proposal: Account<'info, Proposal>,
     
-    pub system_program: Program<'info, System>,
+    pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct VoteOnProposal<'info> {
-    #[account(mut)]
-    pub proposal: Account<'info, Proposal>,
-    
+pub struct ExecuteProposal<'info> {
     #[account(
-        seeds = [b"stake", voter.key().as_ref()],
+        seeds = [b"config"],
         bump
     )]
-    pub stake_account: Account<'info, StakeAccount>,
+    pub config: Account<'info, TokenConfig>,
+    
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
     
     #[account(
-        init,
-        payer = voter,
-        space = 8 + VoteRecord::INIT_SPACE,
-        seeds = [b"vote", proposal.key().as_ref(), voter.key().as_ref()],
+        seeds = [b"governance"],
         bump
     )]
-    pub vote_record: Account<'info, VoteRecord>,
+    /// CHECK: PDA accepted as an authority by the target programs
+    pub governance: UncheckedAccount<'info>,
     
+    /// CHECK: Config account of the target program, validated by that program
     #[account(mut)]
-    pub voter: Signer<'info>,
+    pub target_config: UncheckedAccount<'info>,
     
-    pub system_program: Program<'info, System>,
+    /// CHECK: Checked against the proposal's action
+    pub target_program: UncheckedAccount<'info>,
+    
+    pub executor: Signer<'info>,
 }
 
 #[account]
@@ -499,6 +1562,33 @@ pub struct TokenConfig {
     pub min_stake_amount: u64,
     pub voting_period: i64,
     pub proposal_threshold: u64,
+    pub quorum_bps: u16, // Share of circulating supply that must vote
+    pub pass_threshold_bps: u16, // Share of votes cast that must be yes
+    pub execution_delay: i64, // Seconds after voting ends before an action can run
+    pub distributed: [u64; 7], // Cumulative amount minted per DistributionType
+    pub total_staked: u64,
+    pub reward_rate: u64, // FAM per second streamed to stakers
+    pub acc_reward_per_share: u128, // Scaled by REWARD_PRECISION
+    pub last_reward_time: i64,
+    pub unclaimed_rewards: u64, // Accrued to stakers but still in the reward vault
+    pub unbonding_period: i64, // Seconds between request_unstake and withdraw_unbonded
+}
+
+impl TokenConfig {
+    /// Accrue rewards since the last update, never promising more than the
+    /// reward vault holds beyond what stakers are already owed
+    pub fn update_rewards(&mut self, reward_vault_balance: u64, current_time: i64) {
+        let elapsed = (current_time - self.last_reward_time).max(0) as u64;
+        self.last_reward_time = current_time;
+        if self.total_staked == 0 || elapsed == 0 {
+            return;
+        }
+        
+        let available = reward_vault_balance.saturating_sub(self.unclaimed_rewards);
+        let reward = self.reward_rate.saturating_mul(elapsed).min(available);
+        self.acc_reward_per_share += reward as u128 * REWARD_PRECISION / self.total_staked as u128;
+        self.unclaimed_rewards += reward;
+    }
 }
 
 #[account]
@@ -506,8 +1596,176 @@ pub struct TokenConfig {
 pub struct StakeAccount {
     pub user: Pubkey,
     pub staked_amount: u64,
-    pub voting_power: u64,
+    pub voting_power: u64, // As of the last stake change; decays with the lock
     pub last_stake_time: i64,
+    pub lock_end: i64,
+    pub delegate: Option<Pubkey>,
+    pub reward_debt: u128, // staked_amount * acc_reward_per_share at the last settlement
+    pub pending_rewards: u64,
+    #[max_len(5)]
+    pub unbonding: Vec<UnbondingEntry>,
+    #[max_len(32)]
+    pub checkpoints: Vec<StakeCheckpoint>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, InitSpace)]
+pub struct UnbondingEntry {
+    pub amount: u64,
+    pub release_time: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, InitSpace)]
+pub struct StakeCheckpoint {
+    pub timestamp: i64,
+    pub staked_amount: u64,
+    pub lock_end: i64,
+    pub is_delegated: bool,
+}
+
+/// Voting power other stakers delegated to `delegate`, owned by the
+/// delegate so delegators can't rewrite the delegate's own stake history
+#[account]
+#[derive(InitSpace)]
+pub struct DelegatePower {
+    pub delegate: Pubkey,
+    pub delegated_amount: u64, // Counted at 1x, without the delegators' lock boost
+    pub delegator_count: u32,
+    #[max_len(32)]
+    pub checkpoints: Vec<DelegateCheckpoint>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, InitSpace)]
+pub struct DelegateCheckpoint {
+    pub timestamp: i64,
+    pub delegated_amount: u64,
+}
+
+/// A point in a stake or delegation history, looked up by proposal creation time
+pub trait Checkpoint: Copy {
+    fn timestamp(&self) -> i64;
+}
+
+impl Checkpoint for StakeCheckpoint {
+    fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+}
+
+impl Checkpoint for DelegateCheckpoint {
+    fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+}
+
+/// Append `checkpoint`, replacing one from the same timestamp. Entries are
+/// only dropped once no proposal still open to votes can look them up:
+/// votes close at most `MAX_VOTING_PERIOD` after creation, whatever the
+/// voting period was then, so only the newest entry before
+/// `timestamp - MAX_VOTING_PERIOD` and those after it stay reachable.
+fn record_checkpoint<T: Checkpoint>(
+    history: &mut Vec<T>,
+    checkpoint: T,
+    capacity: usize,
+) -> Result<()> {
+    let horizon = checkpoint.timestamp() - MAX_VOTING_PERIOD;
+    let unreachable = history
+        .iter()
+        .rposition(|entry| entry.timestamp() < horizon)
+        .unwrap_or(0);
+    history.drain(..unreachable);
+    
+    if let Some(last) = history.last_mut() {
+        if last.timestamp() == checkpoint.timestamp() {
+            *last = checkpoint;
+            return Ok(());
+        }
+    }
+    
+    require!(history.len() < capacity, ErrorCode::CheckpointHistoryFull);
+    history.push(checkpoint);
+    Ok(())
+}
+
+/// Latest entry recorded strictly before `timestamp`
+fn checkpoint_before<T: Checkpoint>(history: &[T], timestamp: i64) -> Option<T> {
+    history
+        .iter()
+        .rev()
+        .find(|checkpoint| checkpoint.timestamp() < timestamp)
+        .copied()
+}
+
+impl DelegatePower {
+    /// Record the current delegated total
+    pub fn record_checkpoint(&mut self, timestamp: i64) -> Result<()> {
+        let checkpoint = DelegateCheckpoint {
+            timestamp,
+            delegated_amount: self.delegated_amount,
+        };
+        record_checkpoint(&mut self.checkpoints, checkpoint, MAX_DELEGATE_CHECKPOINTS)
+    }
+    
+    /// Delegated total recorded strictly before `timestamp`
+    pub fn checkpoint_before(&self, timestamp: i64) -> Option<DelegateCheckpoint> {
+        checkpoint_before(&self.checkpoints, timestamp)
+    }
+}
+
+impl StakeAccount {
+    /// Move rewards earned since the last settlement into `pending_rewards`
+    pub fn settle_rewards(&mut self, config: &TokenConfig) {
+        let accrued = self.staked_amount as u128 * config.acc_reward_per_share / REWARD_PRECISION;
+        self.pending_rewards += accrued.saturating_sub(self.reward_debt) as u64;
+    }
+    
+    /// Call after `staked_amount` changes so only future accrual is owed
+    pub fn reset_reward_debt(&mut self, config: &TokenConfig) {
+        self.reward_debt = self.staked_amount as u128 * config.acc_reward_per_share / REWARD_PRECISION;
+    }
+    
+    /// Record the current stake
+    pub fn record_checkpoint(&mut self, timestamp: i64) -> Result<()> {
+        let checkpoint = StakeCheckpoint {
+            timestamp,
+            staked_amount: self.staked_amount,
+            lock_end: self.lock_end,
+            is_delegated: self.delegate.is_some(),
+        };
+        record_checkpoint(&mut self.checkpoints, checkpoint, MAX_STAKE_CHECKPOINTS)
+    }
+    
+    /// Latest stake state recorded strictly before `timestamp`
+    pub fn checkpoint_before(&self, timestamp: i64) -> Option<StakeCheckpoint> {
+        checkpoint_before(&self.checkpoints, timestamp)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct VestingAccount {
+    pub beneficiary: Pubkey,
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub start_time: i64,
+    pub cliff_time: i64,
+    pub duration: i64,
+    pub revocable: bool,
+    pub revoked: bool,
+    pub index: u64, // Distinguishes multiple grants to the same beneficiary
+}
+
+impl VestingAccount {
+    /// Linear vesting from `start_time` over `duration`, nothing before the cliff
+    pub fn vested_amount(&self, current_time: i64) -> u64 {
+        if self.revoked {
+            return self.total_amount;
+        }
+        if current_time < self.cliff_time {
+            return 0;
+        }
+        let elapsed = (current_time - self.start_time).clamp(0, self.duration);
+        (self.total_amount as u128 * elapsed as u128 / self.duration as u128) as u64
+    }
 }
 
 #[account]
@@ -524,6 +1782,7 @@ pub struct Proposal {
     pub yes_votes: u64,
     pub no_votes: u64,
     pub status: ProposalStatus,
+    pub action: Option<GovernanceAction>,
 }
 
 #[account]
@@ -547,6 +1806,26 @@ pub enum DistributionType {
     Marketing,
 }
 
+impl DistributionType {
+    pub const ALL: [DistributionType; DISTRIBUTION_TYPE_COUNT] = [
+        DistributionType::TeamVesting,
+        DistributionType::PublicSale,
+        DistributionType::LiquidityBootstrap,
+        DistributionType::EcosystemIncentives,
+        DistributionType::CommunityTreasury,
+        DistributionType::StakingRewards,
+        DistributionType::Marketing,
+    ];
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct DistributionAllocation {
+    pub distribution_type: DistributionType,
+    pub cap: u64,
+    pub distributed: u64,
+    pub remaining: u64,
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, InitSpace)]
 pub enum ProposalType {
     EconomicParameter,
@@ -556,6 +1835,28 @@ pub enum ProposalType {
     Emergency,
 }
 
+/// Parameter change applied by `execute_proposal` through the target program's `update_config`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, InitSpace)]
+pub enum GovernanceAction {
+    MarketplaceFeeRate(u16), // game_treasury, basis points
+    TerritoryTaxRate(u16),   // game_treasury, basis points
+    DailyEmission(u64),      // mob_token
+    AttackCooldown(i64),     // turf_control, seconds
+    StakingRewardVault(Pubkey), // game_treasury, FAM reward vault
+}
+
+impl GovernanceAction {
+    pub fn target_program(&self) -> Pubkey {
+        match self {
+            GovernanceAction::MarketplaceFeeRate(_)
+            | GovernanceAction::TerritoryTaxRate(_)
+            | GovernanceAction::StakingRewardVault(_) => game_treasury::ID,
+            GovernanceAction::DailyEmission(_) => mob_token::ID,
+            GovernanceAction::AttackCooldown(_) => turf_control::ID,
+        }
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, InitSpace)]
 pub enum ProposalStatus {
     Active,
@@ -571,7 +1872,7 @@ pub enum Vote {
     No,
 }
 
-fn validate_distribution(amount: u64, distribution_type: &DistributionType, total_supply: u64) -> Result<()> {
+fn distribution_cap(distribution_type: &DistributionType, total_supply: u64) -> u64 {
     let max_percentage = match distribution_type {
         DistributionType::TeamVesting => 20, // 20%
         DistributionType::PublicSale => 15, // 15%
@@ -582,15 +1883,24 @@ fn validate_distribution(amount: u64, distribution_type: &DistributionType, tota
         DistributionType::Marketing => 5, // 5%
     };
     
-    let max_amount = (total_supply * max_percentage) / 100;
-    require!(amount <= max_amount, ErrorCode::ExceedsDistributionLimit);
+    (total_supply * max_percentage) / 100
+}
+
+fn validate_distribution(amount: u64, distribution_type: &DistributionType, config: &TokenConfig) -> Result<()> {
+    let distributed = config.distributed[*distribution_type as usize];
+    let max_amount = distribution_cap(distribution_type, config.total_supply);
+    require!(
+        distributed.checked_add(amount).is_some_and(|total| total <= max_amount),
+        ErrorCode::ExceedsDistributionLimit
+    );
     Ok(())
 }
 
-fn calculate_voting_power(staked_amount: u64, _current_time: i64) -> u64 {
-    // Simple 1:1 voting power for now
-    // Could add time-based multipliers later
-    staked_amount
+fn calculate_voting_power(staked_amount: u64, lock_end: i64, current_time: i64) -> u64 {
+    // Vote-escrow boost: up to 4x at a 2 year lock, decaying linearly to 1x at unlock
+    let remaining = (lock_end - current_time).clamp(0, MAX_LOCK_DURATION) as u128;
+    let multiplier_bps = 10000 + MAX_LOCK_BOOST_BPS * remaining / MAX_LOCK_DURATION as u128;
+    (staked_amount as u128 * multiplier_bps / 10000) as u64
 }
 
 #[event]
@@ -601,12 +1911,51 @@ pub struct TokensDistributed {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct VestingCreated {
+    pub beneficiary: Pubkey,
+    pub index: u64,
+    pub amount: u64,
+    pub start_time: i64,
+    pub cliff_time: i64,
+    pub duration: i64,
+    pub revocable: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestedTokensClaimed {
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub total_claimed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestingRevoked {
+    pub beneficiary: Pubkey,
+    pub vested_amount: u64,
+    pub burned_amount: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct TokensStaked {
     pub user: Pubkey,
     pub amount: u64,
     pub total_staked: u64,
     pub voting_power: u64,
+    pub lock_end: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UnstakeRequested {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub release_time: i64,
+    pub remaining_staked: u64,
+    pub voting_power: u64,
     pub timestamp: i64,
 }
 
@@ -619,6 +1968,19 @@ pub struct TokensUnstaked {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct StakingRewardsClaimed {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardRateUpdated {
+    pub reward_rate: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct ProposalCreated {
     pub proposal_id: Pubkey,
@@ -637,6 +1999,54 @@ pub struct VoteCast {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct DelegateRegistered {
+    pub delegate: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VotesDelegated {
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+    pub amount: u64,
+    pub delegate_total: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelegationRevoked {
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+    pub amount: u64,
+    pub delegate_total: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalFinalized {
+    pub proposal_id: Pubkey,
+    pub status: ProposalStatus,
+    pub yes_votes: u64,
+    pub no_votes: u64,
+    pub quorum_reached: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalCancelled {
+    pub proposal_id: Pubkey,
+    pub cancelled_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub proposal_id: Pubkey,
+    pub executed_by: Pubkey,
+    pub timestamp: i64,
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("Program is currently inactive")]
@@ -667,4 +2077,52 @@ pub enum ErrorCode {
     NoVotingPower,
     #[msg("Already voted on this proposal")]
     AlreadyVoted,
+    #[msg("Voting period is still active")]
+    VotingPeriodActive,
+    #[msg("Proposal has not passed")]
+    ProposalNotPassed,
+    #[msg("Proposal can no longer be cancelled")]
+    ProposalNotCancellable,
+    #[msg("Only economic parameter proposals can carry an action")]
+    ActionNotAllowed,
+    #[msg("Proposal has no executable action")]
+    NoExecutableAction,
+    #[msg("Proposal action must be executed on-chain")]
+    ExecutableActionPending,
+    #[msg("Execution timelock is still active")]
+    TimelockActive,
+    #[msg("Target program does not match the proposal action")]
+    InvalidTargetProgram,
+    #[msg("Lock duration must be between 1 week and 2 years")]
+    InvalidLockDuration,
+    #[msg("Stake is locked until its lock ends")]
+    StakeLocked,
+    #[msg("Revoke the active delegation first")]
+    DelegationActive,
+    #[msg("No active delegation")]
+    NoActiveDelegation,
+    #[msg("Cannot delegate to yourself")]
+    CannotDelegateToSelf,
+    #[msg("Team allocations must be distributed through a vesting schedule")]
+    VestingRequired,
+    #[msg("Invalid vesting schedule")]
+    InvalidVestingSchedule,
+    #[msg("Nothing to claim")]
+    NothingToClaim,
+    #[msg("Vesting is not revocable")]
+    VestingNotRevocable,
+    #[msg("Vesting has already been revoked")]
+    VestingAlreadyRevoked,
+    #[msg("Too many pending unbonding entries")]
+    TooManyUnbondingEntries,
+    #[msg("Unbonding period cannot be negative")]
+    InvalidUnbondingPeriod,
+    #[msg("No unbonding entries have matured yet")]
+    UnbondingPeriodActive,
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+    #[msg("Checkpoint history is full until older entries age out of the voting period")]
+    CheckpointHistoryFull,
+    #[msg("Voting period or execution delay is out of range")]
+    InvalidGovernancePeriod,
 }
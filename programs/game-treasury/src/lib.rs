@@ -6,6 +6,14 @@ use anchor_spl::{
 
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnX");
 
+const MAX_WITHDRAWAL_SIGNERS: usize = 10;
+const MIN_WITHDRAWAL_DELAY: i64 = 86400; // 24 hours
+const MAX_DISTRIBUTORS: usize = 10;
+const REWARD_TYPE_COUNT: usize = 6;
+const MAX_EPOCH_DURATION: i64 = 2_592_000; // 30 days
+const MAX_REWARD_TYPE_BUDGET: u64 = 50_000_000_000_000; // 50,000 MOB per epoch
+const MAX_RECIPIENT_DAILY_CAP: u64 = 1_000_000_000_000; // 1,000 MOB
+
 #[program]
 pub mod game_treasury {
     use super::*;
@@ -21,6 +29,26 @@ pub mod game_treasury {
         treasury.marketplace_fee_rate = 250; // 2.5%
         treasury.territory_tax_rate = 2000; // 20%
         treasury.is_active = true;
+        treasury.governance = Pubkey::default();
+        treasury.staking_reward_vault = Pubkey::default();
+        treasury.withdrawal_signers = Vec::new();
+        treasury.withdrawal_threshold = 0;
+        treasury.guardian = Pubkey::default();
+        treasury.withdrawal_delay = 172800; // 48 hours
+        treasury.withdrawal_count = 0;
+        treasury.distributors = Vec::new();
+        treasury.epoch_duration = 86400; // 24 hours
+        treasury.epoch_start = Clock::get()?.unix_timestamp;
+        treasury.reward_type_budgets = [
+            10_000_000_000_000, // Mission: 10,000 MOB
+            5_000_000_000_000,  // PvpVictory: 5,000 MOB
+            2_500_000_000_000,  // TerritoryDefense: 2,500 MOB
+            5_000_000_000_000,  // Achievement: 5,000 MOB
+            10_000_000_000_000, // Daily: 10,000 MOB
+            5_000_000_000_000,  // Tournament: 5,000 MOB
+        ];
+        treasury.reward_type_distributed = [0; REWARD_TYPE_COUNT];
+        treasury.recipient_daily_cap = 25_000_000_000; // 25 MOB
         
         msg!("Game treasury initialized");
         Ok(())
@@ -104,13 +132,47 @@ pub mod game_treasury {
         reward_type: RewardType,
     ) -> Result<()> {
         let treasury = &mut ctx.accounts.treasury;
+        let current_time = Clock::get()?.unix_timestamp;
         
         require!(treasury.is_active, ErrorCode::TreasuryInactive);
+        require!(
+            treasury.distributors.contains(&ctx.accounts.distributor.key()),
+            ErrorCode::UnauthorizedDistributor
+        );
         require!(amount > 0, ErrorCode::InvalidAmount);
         
         // Validate reward amount based on type
         validate_reward_distribution(amount, &reward_type)?;
         
+        // Enforce the per-type epoch budget
+        treasury.roll_epoch(current_time);
+        let type_index = reward_type as usize;
+        let type_distributed = treasury.reward_type_distributed[type_index]
+            .checked_add(amount)
+            .ok_or(ErrorCode::EpochBudgetExceeded)?;
+        require!(
+            type_distributed <= treasury.reward_type_budgets[type_index],
+            ErrorCode::EpochBudgetExceeded
+        );
+        
+        // Enforce the per-recipient daily cap
+        let recipient_rewards = &mut ctx.accounts.recipient_rewards;
+        if recipient_rewards.recipient == Pubkey::default() {
+            recipient_rewards.recipient = ctx.accounts.recipient.key();
+        }
+        if current_time - recipient_rewards.day_start >= 86400 { // 24 hours
+            recipient_rewards.day_start = current_time;
+            recipient_rewards.received_today = 0;
+        }
+        let received_today = recipient_rewards
+            .received_today
+            .checked_add(amount)
+            .ok_or(ErrorCode::RecipientDailyCapExceeded)?;
+        require!(
+            received_today <= treasury.recipient_daily_cap,
+            ErrorCode::RecipientDailyCapExceeded
+        );
+        
         // Transfer rewards from treasury
         let seeds = &[
             b"treasury_authority",
@@ -129,18 +191,77 @@ pub mod game_treasury {
         token::transfer(cpi_ctx, amount)?;
         
         treasury.total_rewards_distributed += amount;
+        treasury.reward_type_distributed[type_index] = type_distributed;
+        recipient_rewards.received_today = received_today;
+        recipient_rewards.total_received += amount;
         
         emit!(RewardDistributed {
             recipient: ctx.accounts.recipient.key(),
+            distributor: ctx.accounts.distributor.key(),
             amount,
             reward_type,
-            timestamp: Clock::get()?.unix_timestamp,
+            timestamp: current_time,
         });
         
         msg!("Distributed {} MOB as {:?} reward", amount, reward_type);
         Ok(())
     }
 
+    /// Authorize a reward distributor (admin only)
+    pub fn add_distributor(ctx: Context<ManageDistributors>, distributor: Pubkey) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        
+        require!(
+            ctx.accounts.authority.key() == treasury.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            !treasury.distributors.contains(&distributor),
+            ErrorCode::DistributorAlreadyExists
+        );
+        require!(
+            treasury.distributors.len() < MAX_DISTRIBUTORS,
+            ErrorCode::TooManyDistributors
+        );
+        
+        treasury.distributors.push(distributor);
+        
+        emit!(DistributorAdded {
+            distributor,
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Reward distributor added: {}", distributor);
+        Ok(())
+    }
+
+    /// Revoke a reward distributor (admin only)
+    pub fn remove_distributor(ctx: Context<ManageDistributors>, distributor: Pubkey) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        
+        require!(
+            ctx.accounts.authority.key() == treasury.authority,
+            ErrorCode::Unauthorized
+        );
+        
+        let index = treasury
+            .distributors
+            .iter()
+            .position(|key| *key == distributor)
+            .ok_or(ErrorCode::DistributorNotFound)?;
+        treasury.distributors.remove(index);
+        
+        emit!(DistributorRemoved {
+            distributor,
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Reward distributor removed: {}", distributor);
+        Ok(())
+    }
+
     /// Distribute FAM staking rewards
     pub fn distribute_staking_rewards(
         ctx: Context<DistributeStakingRewards>,
@@ -149,6 +270,10 @@ pub mod game_treasury {
         let treasury = &mut ctx.accounts.treasury;
         
         require!(treasury.is_active, ErrorCode::TreasuryInactive);
+        require!(
+            ctx.accounts.authority.key() == treasury.authority,
+            ErrorCode::Unauthorized
+        );
         require!(amount > 0, ErrorCode::InvalidAmount);
         
         // Transfer FAM tokens from treasury
@@ -160,7 +285,7 @@ pub mod game_treasury {
         
         let cpi_accounts = Transfer {
             from: ctx.accounts.treasury_fam_account.to_account_info(),
-            to: ctx.accounts.recipient_fam_account.to_account_info(),
+            to: ctx.accounts.staking_reward_vault.to_account_info(),
             authority: ctx.accounts.treasury_authority.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
@@ -169,7 +294,7 @@ pub mod game_treasury {
         token::transfer(cpi_ctx, amount)?;
         
         emit!(StakingRewardDistributed {
-            recipient: ctx.accounts.recipient.key(),
+            recipient: ctx.accounts.staking_reward_vault.key(),
             amount,
             timestamp: Clock::get()?.unix_timestamp,
         });
@@ -178,11 +303,14 @@ pub mod game_treasury {
         Ok(())
     }
 
-    /// Emergency withdraw (admin only)
-    pub fn emergency_withdraw(
-        ctx: Context<EmergencyWithdraw>,
-        amount: u64,
-        token_type: TokenType,
+    /// Bootstrap the emergency withdrawal council (admin only, once).
+    /// Later changes go through propose_council_update and the withdrawal timelock.
+    pub fn set_withdrawal_council(
+        ctx: Context<SetWithdrawalCouncil>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+        guardian: Pubkey,
+        withdrawal_delay: i64,
     ) -> Result<()> {
         let treasury = &mut ctx.accounts.treasury;
         
@@ -190,47 +318,281 @@ pub mod game_treasury {
             ctx.accounts.authority.key() == treasury.authority,
             ErrorCode::Unauthorized
         );
+        require!(treasury.withdrawal_signers.is_empty(), ErrorCode::CouncilAlreadySet);
+        
+        let council = CouncilUpdate {
+            signers,
+            threshold,
+            guardian,
+            withdrawal_delay,
+        };
+        council.validate()?;
+        council.apply(treasury)?;
+        
+        msg!("Withdrawal council bootstrapped: {} signer threshold", threshold);
+        Ok(())
+    }
+
+    /// Propose replacing the council (council signers only). It needs the same
+    /// approvals, timelock and guardian veto window as an emergency withdrawal.
+    pub fn propose_council_update(
+        ctx: Context<ProposeWithdrawal>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+        guardian: Pubkey,
+        withdrawal_delay: i64,
+    ) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        let withdrawal = &mut ctx.accounts.withdrawal;
+        let proposer = ctx.accounts.proposer.key();
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        require!(
+            treasury.withdrawal_signers.contains(&proposer),
+            ErrorCode::NotWithdrawalSigner
+        );
+        
+        let council = CouncilUpdate {
+            signers,
+            threshold,
+            guardian,
+            withdrawal_delay,
+        };
+        council.validate()?;
+        
+        withdrawal.id = treasury.withdrawal_count;
+        withdrawal.proposer = proposer;
+        withdrawal.token_type = TokenType::MOB;
+        withdrawal.amount = 0;
+        withdrawal.recipient = Pubkey::default();
+        withdrawal.approvals = vec![proposer];
+        withdrawal.created_at = current_time;
+        withdrawal.executable_at = 0;
+        withdrawal.status = WithdrawalStatus::Pending;
+        if withdrawal.current_approvals(&treasury.withdrawal_signers)
+            >= treasury.withdrawal_threshold as usize
+        {
+            withdrawal.executable_at = current_time + treasury.withdrawal_delay;
+        }
+        
+        treasury.withdrawal_count += 1;
+        
+        emit!(CouncilUpdateProposed {
+            withdrawal_id: withdrawal.id,
+            proposer,
+            signers: council.signers.clone(),
+            threshold: council.threshold,
+            guardian: council.guardian,
+            withdrawal_delay: council.withdrawal_delay,
+            executable_at: withdrawal.executable_at,
+            timestamp: current_time,
+        });
+        
+        withdrawal.council_update = Some(council);
+        
+        msg!("Council update {} proposed", withdrawal.id);
+        Ok(())
+    }
+
+    /// Apply an approved council update once its delay has passed
+    pub fn execute_council_update(ctx: Context<ExecuteCouncilUpdate>) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        let withdrawal = &mut ctx.accounts.withdrawal;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        require!(
+            withdrawal.status == WithdrawalStatus::Pending,
+            ErrorCode::WithdrawalNotPending
+        );
+        require!(withdrawal.executable_at != 0, ErrorCode::InsufficientApprovals);
+        require!(current_time >= withdrawal.executable_at, ErrorCode::WithdrawalDelayActive);
+        require!(
+            withdrawal.current_approvals(&treasury.withdrawal_signers)
+                >= treasury.withdrawal_threshold as usize,
+            ErrorCode::InsufficientApprovals
+        );
+        
+        let council = withdrawal
+            .council_update
+            .clone()
+            .ok_or(ErrorCode::NotCouncilUpdate)?;
+        council.apply(treasury)?;
+        
+        withdrawal.status = WithdrawalStatus::Executed;
+        
+        msg!("Council update {} executed", withdrawal.id);
+        Ok(())
+    }
+
+    /// Propose an emergency withdrawal (council signers only)
+    pub fn propose_withdrawal(
+        ctx: Context<ProposeWithdrawal>,
+        amount: u64,
+        token_type: TokenType,
+        recipient: Pubkey,
+    ) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        let withdrawal = &mut ctx.accounts.withdrawal;
+        let proposer = ctx.accounts.proposer.key();
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        require!(
+            treasury.withdrawal_signers.contains(&proposer),
+            ErrorCode::NotWithdrawalSigner
+        );
         require!(amount > 0, ErrorCode::InvalidAmount);
         
+        withdrawal.id = treasury.withdrawal_count;
+        withdrawal.proposer = proposer;
+        withdrawal.token_type = token_type;
+        withdrawal.amount = amount;
+        withdrawal.recipient = recipient;
+        withdrawal.approvals = vec![proposer];
+        withdrawal.created_at = current_time;
+        // The timelock only starts once the approval threshold is reached
+        withdrawal.executable_at = 0;
+        withdrawal.status = WithdrawalStatus::Pending;
+        withdrawal.council_update = None;
+        if withdrawal.current_approvals(&treasury.withdrawal_signers)
+            >= treasury.withdrawal_threshold as usize
+        {
+            withdrawal.executable_at = current_time + treasury.withdrawal_delay;
+        }
+        
+        treasury.withdrawal_count += 1;
+        
+        emit!(WithdrawalProposed {
+            withdrawal_id: withdrawal.id,
+            proposer,
+            amount,
+            token_type,
+            recipient,
+            executable_at: withdrawal.executable_at,
+            timestamp: current_time,
+        });
+        
+        msg!("Emergency withdrawal {} proposed: {} {:?}", withdrawal.id, amount, token_type);
+        Ok(())
+    }
+
+    /// Approve a pending emergency withdrawal (council signers only)
+    pub fn approve_withdrawal(ctx: Context<ApproveWithdrawal>) -> Result<()> {
+        let treasury = &ctx.accounts.treasury;
+        let withdrawal = &mut ctx.accounts.withdrawal;
+        let signer = ctx.accounts.signer.key();
+        
+        require!(
+            treasury.withdrawal_signers.contains(&signer),
+            ErrorCode::NotWithdrawalSigner
+        );
+        require!(
+            withdrawal.status == WithdrawalStatus::Pending,
+            ErrorCode::WithdrawalNotPending
+        );
+        require!(!withdrawal.approvals.contains(&signer), ErrorCode::AlreadyApproved);
+        
+        let current_time = Clock::get()?.unix_timestamp;
+        withdrawal.approvals.push(signer);
+        
+        // Start the timelock when the threshold is first reached
+        let approvals = withdrawal.current_approvals(&treasury.withdrawal_signers);
+        if withdrawal.executable_at == 0 && approvals >= treasury.withdrawal_threshold as usize {
+            withdrawal.executable_at = current_time + treasury.withdrawal_delay;
+        }
+        
+        emit!(WithdrawalApproved {
+            withdrawal_id: withdrawal.id,
+            signer,
+            approvals: approvals as u8,
+            executable_at: withdrawal.executable_at,
+            timestamp: current_time,
+        });
+        
+        msg!("Emergency withdrawal {} approved by {}", withdrawal.id, signer);
+        Ok(())
+    }
+
+    /// Veto a pending emergency withdrawal (guardian only)
+    pub fn veto_withdrawal(ctx: Context<VetoWithdrawal>) -> Result<()> {
+        let treasury = &ctx.accounts.treasury;
+        let withdrawal = &mut ctx.accounts.withdrawal;
+        
+        require!(
+            treasury.guardian != Pubkey::default()
+                && ctx.accounts.guardian.key() == treasury.guardian,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            withdrawal.status == WithdrawalStatus::Pending,
+            ErrorCode::WithdrawalNotPending
+        );
+        
+        withdrawal.status = WithdrawalStatus::Vetoed;
+        
+        emit!(WithdrawalVetoed {
+            withdrawal_id: withdrawal.id,
+            guardian: ctx.accounts.guardian.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Emergency withdrawal {} vetoed", withdrawal.id);
+        Ok(())
+    }
+
+    /// Execute an approved emergency withdrawal once its delay has passed
+    pub fn execute_withdrawal(ctx: Context<ExecuteWithdrawal>) -> Result<()> {
+        let treasury = &ctx.accounts.treasury;
+        let withdrawal = &mut ctx.accounts.withdrawal;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        require!(
+            withdrawal.status == WithdrawalStatus::Pending,
+            ErrorCode::WithdrawalNotPending
+        );
+        require!(withdrawal.council_update.is_none(), ErrorCode::NotWithdrawal);
+        require!(withdrawal.executable_at != 0, ErrorCode::InsufficientApprovals);
+        require!(current_time >= withdrawal.executable_at, ErrorCode::WithdrawalDelayActive);
+        
+        // Only approvals from the current signer set count
+        require!(
+            withdrawal.current_approvals(&treasury.withdrawal_signers)
+                >= treasury.withdrawal_threshold as usize,
+            ErrorCode::InsufficientApprovals
+        );
+        
+        let expected_mint = match withdrawal.token_type {
+            TokenType::MOB => treasury.mob_mint,
+            TokenType::FAM => treasury.fam_mint,
+        };
+        require!(ctx.accounts.mint.key() == expected_mint, ErrorCode::InvalidMint);
+        
         let seeds = &[
             b"treasury_authority",
             &[ctx.bumps.treasury_authority],
         ];
         let signer = &[&seeds[..]];
         
-        match token_type {
-            TokenType::MOB => {
-                let cpi_accounts = Transfer {
-                    from: ctx.accounts.treasury_token_account.to_account_info(),
-                    to: ctx.accounts.authority_token_account.to_account_info(),
-                    authority: ctx.accounts.treasury_authority.to_account_info(),
-                };
-                let cpi_program = ctx.accounts.token_program.to_account_info();
-                let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-                
-                token::transfer(cpi_ctx, amount)?;
-            }
-            TokenType::FAM => {
-                let cpi_accounts = Transfer {
-                    from: ctx.accounts.treasury_fam_account.to_account_info(),
-                    to: ctx.accounts.authority_fam_account.to_account_info(),
-                    authority: ctx.accounts.treasury_authority.to_account_info(),
-                };
-                let cpi_program = ctx.accounts.token_program.to_account_info();
-                let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-                
-                token::transfer(cpi_ctx, amount)?;
-            }
-        }
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.treasury_token_account.to_account_info(),
+            to: ctx.accounts.recipient_token_account.to_account_info(),
+            authority: ctx.accounts.treasury_authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
         
-        emit!(EmergencyWithdrawal {
-            authority: ctx.accounts.authority.key(),
-            amount,
-            token_type,
-            timestamp: Clock::get()?.unix_timestamp,
+        token::transfer(cpi_ctx, withdrawal.amount)?;
+        
+        withdrawal.status = WithdrawalStatus::Executed;
+        
+        emit!(WithdrawalExecuted {
+            withdrawal_id: withdrawal.id,
+            recipient: withdrawal.recipient,
+            amount: withdrawal.amount,
+            token_type: withdrawal.token_type,
+            timestamp: current_time,
         });
         
-        msg!("Emergency withdrawal: {} {:?}", amount, token_type);
+        msg!("Emergency withdrawal {}: {} {:?}", withdrawal.id, withdrawal.amount, withdrawal.token_type);
         Ok(())
     }
 
@@ -240,11 +602,15 @@ pub mod game_treasury {
         new_marketplace_fee_rate: Option<u16>,
         new_territory_tax_rate: Option<u16>,
         new_is_active: Option<bool>,
+        new_governance: Option<Pubkey>,
+        new_staking_reward_vault: Option<Pubkey>,
     ) -> Result<()> {
         let treasury = &mut ctx.accounts.treasury;
+        let authority = ctx.accounts.authority.key();
         
+        // The governance PDA may tune parameters
         require!(
-            ctx.accounts.authority.key() == treasury.authority,
+            authority == treasury.authority || authority == treasury.governance,
             ErrorCode::Unauthorized
         );
         
@@ -262,16 +628,97 @@ pub mod game_treasury {
             treasury.is_active = active;
         }
         
+        // The admin links governance once; afterwards only governance can hand it over
+        if let Some(governance) = new_governance {
+            let bootstrap = treasury.governance == Pubkey::default()
+                && authority == treasury.authority;
+            require!(
+                bootstrap || authority == treasury.governance,
+                ErrorCode::Unauthorized
+            );
+            treasury.governance = governance;
+        }
+        
+        // The admin sets the first vault; re-pointing it afterwards needs a
+        // timelocked governance proposal
+        if let Some(vault) = new_staking_reward_vault {
+            let bootstrap = treasury.staking_reward_vault == Pubkey::default()
+                && authority == treasury.authority;
+            require!(
+                bootstrap || authority == treasury.governance,
+                ErrorCode::Unauthorized
+            );
+            treasury.staking_reward_vault = vault;
+        }
+        
         msg!("Treasury configuration updated");
         Ok(())
     }
 
+    /// Update reward epoch budgets and the per-recipient daily cap
+    pub fn update_reward_budgets(
+        ctx: Context<UpdateConfig>,
+        new_epoch_duration: Option<i64>,
+        new_reward_type_budgets: Option<[u64; REWARD_TYPE_COUNT]>,
+        new_recipient_daily_cap: Option<u64>,
+    ) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        let authority = ctx.accounts.authority.key();
+        
+        require!(
+            authority == treasury.authority || authority == treasury.governance,
+            ErrorCode::Unauthorized
+        );
+        
+        if let Some(duration) = new_epoch_duration {
+            require!(
+                duration > 0 && duration <= MAX_EPOCH_DURATION,
+                ErrorCode::InvalidEpochDuration
+            );
+            treasury.epoch_duration = duration;
+        }
+        
+        if let Some(budgets) = new_reward_type_budgets {
+            require!(
+                budgets.iter().all(|budget| *budget <= MAX_REWARD_TYPE_BUDGET),
+                ErrorCode::ExcessiveRewardBudget
+            );
+            treasury.reward_type_budgets = budgets;
+        }
+        
+        if let Some(cap) = new_recipient_daily_cap {
+            require!(cap <= MAX_RECIPIENT_DAILY_CAP, ErrorCode::ExcessiveRecipientCap);
+            treasury.recipient_daily_cap = cap;
+        }
+        
+        msg!("Treasury reward budgets updated");
+        Ok(())
+    }
+
     /// Get treasury statistics
     pub fn get_treasury_stats(ctx: Context<GetTreasuryStats>) -> Result<TreasuryStats> {
         let treasury = &ctx.accounts.treasury;
         
         let mob_balance = ctx.accounts.treasury_token_account.amount;
         let fam_balance = ctx.accounts.treasury_fam_account.amount;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        // An elapsed epoch reports its full budget even before the next distribution resets it
+        let epoch_elapsed = current_time - treasury.epoch_start >= treasury.epoch_duration;
+        let mut reward_budget_remaining = [0u64; REWARD_TYPE_COUNT];
+        for (index, remaining) in reward_budget_remaining.iter_mut().enumerate() {
+            let distributed = if epoch_elapsed {
+                0
+            } else {
+                treasury.reward_type_distributed[index]
+            };
+            *remaining = treasury.reward_type_budgets[index].saturating_sub(distributed);
+        }
+        let epoch_ends_at = if epoch_elapsed {
+            current_time + treasury.epoch_duration
+        } else {
+            treasury.epoch_start + treasury.epoch_duration
+        };
         
         let stats = TreasuryStats {
             total_fees_collected: treasury.total_fees_collected,
@@ -281,12 +728,15 @@ pub mod game_treasury {
             marketplace_fee_rate: treasury.marketplace_fee_rate,
             territory_tax_rate: treasury.territory_tax_rate,
             is_active: treasury.is_active,
+            epoch_ends_at,
+            reward_budget_remaining,
+            recipient_daily_cap: treasury.recipient_daily_cap,
         };
         
         emit!(TreasuryStatsRequested {
             requester: ctx.accounts.requester.key(),
             stats: stats.clone(),
-            timestamp: Clock::get()?.unix_timestamp,
+            timestamp: current_time,
         });
         
         Ok(stats)
@@ -428,8 +878,34 @@ pub struct DistributeRewards<'info> {
     /// CHECK: Reward recipient
     pub recipient: UncheckedAccount<'info>,
     
+    #[account(
+        init_if_needed,
+        payer = distributor,
+        space = 8 + RecipientRewards::INIT_SPACE,
+        seeds = [b"recipient_rewards", recipient.key().as_ref()],
+        bump
+    )]
+    pub recipient_rewards: Account<'info, RecipientRewards>,
+    
     pub mob_mint: Account<'info, Mint>,
+    
+    #[account(mut)]
+    pub distributor: Signer<'info>,
+    
     pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageDistributors<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    
+    pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
@@ -455,66 +931,149 @@ pub struct DistributeStakingRewards<'info> {
     /// CHECK: PDA used as treasury authority
     pub treasury_authority: UncheckedAccount<'info>,
     
+    /// fam_token reward vault that streams FAM to stakers
     #[account(
         mut,
-        associated_token::mint = fam_mint,
-        associated_token::authority = recipient,
+        address = treasury.staking_reward_vault,
+        token::mint = fam_mint,
     )]
-    pub recipient_fam_account: Account<'info, TokenAccount>,
-    
-    /// CHECK: Reward recipient
-    pub recipient: UncheckedAccount<'info>,
+    pub staking_reward_vault: Account<'info, TokenAccount>,
     
     pub fam_mint: Account<'info, Mint>,
+    pub authority: Signer<'info>,
     pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct EmergencyWithdraw<'info> {
+pub struct SetWithdrawalCouncil<'info> {
     #[account(
+        mut,
         seeds = [b"treasury"],
         bump
     )]
     pub treasury: Account<'info, Treasury>,
     
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeWithdrawal<'info> {
     #[account(
         mut,
-        associated_token::mint = mob_mint,
-        associated_token::authority = treasury_authority,
+        seeds = [b"treasury"],
+        bump
     )]
-    pub treasury_token_account: Account<'info, TokenAccount>,
+    pub treasury: Account<'info, Treasury>,
+    
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + WithdrawalProposal::INIT_SPACE,
+        seeds = [b"withdrawal", treasury.withdrawal_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub withdrawal: Account<'info, WithdrawalProposal>,
+    
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveWithdrawal<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
     
     #[account(
         mut,
-        associated_token::mint = fam_mint,
-        associated_token::authority = treasury_authority,
+        seeds = [b"withdrawal", withdrawal.id.to_le_bytes().as_ref()],
+        bump
     )]
-    pub treasury_fam_account: Account<'info, TokenAccount>,
+    pub withdrawal: Account<'info, WithdrawalProposal>,
     
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VetoWithdrawal<'info> {
     #[account(
-        seeds = [b"treasury_authority"],
+        seeds = [b"treasury"],
         bump
     )]
-    /// CHECK: PDA used as treasury authority
-    pub treasury_authority: UncheckedAccount<'info>,
+    pub treasury: Account<'info, Treasury>,
     
     #[account(
         mut,
-        associated_token::mint = mob_mint,
-        associated_token::authority = authority,
+        seeds = [b"withdrawal", withdrawal.id.to_le_bytes().as_ref()],
+        bump
     )]
-    pub authority_token_account: Account<'info, TokenAccount>,
+    pub withdrawal: Account<'info, WithdrawalProposal>,
     
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteCouncilUpdate<'info> {
     #[account(
         mut,
-        associated_token::mint = fam_mint,
-        associated_token::authority = authority,
+        seeds = [b"treasury"],
+        bump
     )]
-    pub authority_fam_account: Account<'info, TokenAccount>,
+    pub treasury: Account<'info, Treasury>,
     
-    pub mob_mint: Account<'info, Mint>,
-    pub fam_mint: Account<'info, Mint>,
-    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"withdrawal", withdrawal.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub withdrawal: Account<'info, WithdrawalProposal>,
+    
+    pub executor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteWithdrawal<'info> {
+    #[account(
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    
+    #[account(
+        mut,
+        seeds = [b"withdrawal", withdrawal.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub withdrawal: Account<'info, WithdrawalProposal>,
+    
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = treasury_authority,
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"treasury_authority"],
+        bump
+    )]
+    /// CHECK: PDA used as treasury authority
+    pub treasury_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = withdrawal.recipient,
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    
+    /// MOB or FAM mint, matching the proposal's token type
+    pub mint: Account<'info, Mint>,
+    pub executor: Signer<'info>,
     pub token_program: Program<'info, Token>,
 }
 
@@ -573,6 +1132,114 @@ pub struct Treasury {
     pub marketplace_fee_rate: u16, // Basis points (100 = 1%)
     pub territory_tax_rate: u16,   // Basis points (100 = 1%)
     pub is_active: bool,
+    pub governance: Pubkey, // fam_token governance PDA, may update parameters
+    pub staking_reward_vault: Pubkey, // fam_token reward vault funded with staking rewards
+    #[max_len(10)]
+    pub withdrawal_signers: Vec<Pubkey>, // M-of-N council for emergency withdrawals
+    pub withdrawal_threshold: u8,
+    pub guardian: Pubkey, // May veto pending withdrawals
+    pub withdrawal_delay: i64, // Seconds between proposal and execution
+    pub withdrawal_count: u64,
+    #[max_len(10)]
+    pub distributors: Vec<Pubkey>, // Keys allowed to call distribute_rewards
+    pub epoch_duration: i64,
+    pub epoch_start: i64,
+    pub reward_type_budgets: [u64; 6], // Per-epoch MOB budget, indexed by RewardType
+    pub reward_type_distributed: [u64; 6], // Distributed so far this epoch
+    pub recipient_daily_cap: u64,
+}
+
+impl Treasury {
+    /// Start a new reward epoch once the current one has elapsed
+    pub fn roll_epoch(&mut self, now: i64) {
+        if now - self.epoch_start >= self.epoch_duration {
+            self.epoch_start = now;
+            self.reward_type_distributed = [0; REWARD_TYPE_COUNT];
+        }
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RecipientRewards {
+    pub recipient: Pubkey,
+    pub day_start: i64,
+    pub received_today: u64,
+    pub total_received: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct WithdrawalProposal {
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub token_type: TokenType,
+    pub amount: u64,
+    pub recipient: Pubkey,
+    #[max_len(10)]
+    pub approvals: Vec<Pubkey>,
+    pub created_at: i64,
+    pub executable_at: i64, // 0 until the approval threshold is reached
+    pub status: WithdrawalStatus,
+    pub council_update: Option<CouncilUpdate>, // Set when the proposal replaces the council
+}
+
+impl WithdrawalProposal {
+    /// Approvals that come from the current council
+    pub fn current_approvals(&self, signers: &[Pubkey]) -> usize {
+        self.approvals
+            .iter()
+            .filter(|signer| signers.contains(signer))
+            .count()
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, InitSpace)]
+pub struct CouncilUpdate {
+    #[max_len(10)]
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+    pub guardian: Pubkey,
+    pub withdrawal_delay: i64,
+}
+
+impl CouncilUpdate {
+    /// A council needs at least two approvers and a guardian who can veto
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            !self.signers.is_empty() && self.signers.len() <= MAX_WITHDRAWAL_SIGNERS,
+            ErrorCode::InvalidSignerSet
+        );
+        for (index, signer) in self.signers.iter().enumerate() {
+            require!(!self.signers[..index].contains(signer), ErrorCode::InvalidSignerSet);
+        }
+        require!(
+            self.threshold > 1 && self.threshold as usize <= self.signers.len(),
+            ErrorCode::InvalidThreshold
+        );
+        require!(self.guardian != Pubkey::default(), ErrorCode::InvalidGuardian);
+        require!(
+            self.withdrawal_delay >= MIN_WITHDRAWAL_DELAY,
+            ErrorCode::InvalidWithdrawalDelay
+        );
+        Ok(())
+    }
+
+    pub fn apply(self, treasury: &mut Treasury) -> Result<()> {
+        treasury.withdrawal_signers = self.signers.clone();
+        treasury.withdrawal_threshold = self.threshold;
+        treasury.guardian = self.guardian;
+        treasury.withdrawal_delay = self.withdrawal_delay;
+        
+        emit!(WithdrawalCouncilUpdated {
+            signers: self.signers,
+            threshold: self.threshold,
+            guardian: self.guardian,
+            withdrawal_delay: self.withdrawal_delay,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
@@ -584,6 +1251,9 @@ pub struct TreasuryStats {
     pub marketplace_fee_rate: u16,
     pub territory_tax_rate: u16,
     pub is_active: bool,
+    pub epoch_ends_at: i64,
+    pub reward_budget_remaining: [u64; 6], // Indexed by RewardType
+    pub recipient_daily_cap: u64,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
@@ -604,7 +1274,14 @@ pub enum RewardType {
     Tournament,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum WithdrawalStatus {
+    Pending,
+    Executed,
+    Vetoed,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, InitSpace)]
 pub enum TokenType {
     MOB,
     FAM,
@@ -635,11 +1312,26 @@ pub struct FeeCollected {
 #[event]
 pub struct RewardDistributed {
     pub recipient: Pubkey,
+    pub distributor: Pubkey,
     pub amount: u64,
     pub reward_type: RewardType,
     pub timestamp: i64,
 }
 
+#[event]
+pub struct DistributorAdded {
+    pub distributor: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DistributorRemoved {
+    pub distributor: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct StakingRewardDistributed {
     pub recipient: Pubkey,
@@ -648,8 +1340,57 @@ pub struct StakingRewardDistributed {
 }
 
 #[event]
-pub struct EmergencyWithdrawal {
-    pub authority: Pubkey,
+pub struct WithdrawalCouncilUpdated {
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+    pub guardian: Pubkey,
+    pub withdrawal_delay: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CouncilUpdateProposed {
+    pub withdrawal_id: u64,
+    pub proposer: Pubkey,
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+    pub guardian: Pubkey,
+    pub withdrawal_delay: i64,
+    pub executable_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalProposed {
+    pub withdrawal_id: u64,
+    pub proposer: Pubkey,
+    pub amount: u64,
+    pub token_type: TokenType,
+    pub recipient: Pubkey,
+    pub executable_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalApproved {
+    pub withdrawal_id: u64,
+    pub signer: Pubkey,
+    pub approvals: u8,
+    pub executable_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalVetoed {
+    pub withdrawal_id: u64,
+    pub guardian: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalExecuted {
+    pub withdrawal_id: u64,
+    pub recipient: Pubkey,
     pub amount: u64,
     pub token_type: TokenType,
     pub timestamp: i64,
@@ -676,4 +1417,48 @@ pub enum ErrorCode {
     ExcessiveFeeRate,
     #[msg("Excessive tax rate")]
     ExcessiveTaxRate,
+    #[msg("Signer is not on the withdrawal council")]
+    NotWithdrawalSigner,
+    #[msg("Withdrawal signer set is empty, too large or has duplicates")]
+    InvalidSignerSet,
+    #[msg("Threshold must be between 2 and the number of signers")]
+    InvalidThreshold,
+    #[msg("Withdrawal delay is below the minimum")]
+    InvalidWithdrawalDelay,
+    #[msg("Not enough council approvals")]
+    InsufficientApprovals,
+    #[msg("Signer has already approved this withdrawal")]
+    AlreadyApproved,
+    #[msg("Withdrawal is not pending")]
+    WithdrawalNotPending,
+    #[msg("Withdrawal delay has not passed yet")]
+    WithdrawalDelayActive,
+    #[msg("Mint does not match the withdrawal token type")]
+    InvalidMint,
+    #[msg("Signer is not an authorized reward distributor")]
+    UnauthorizedDistributor,
+    #[msg("Distributor is already authorized")]
+    DistributorAlreadyExists,
+    #[msg("Distributor is not authorized")]
+    DistributorNotFound,
+    #[msg("Too many authorized distributors")]
+    TooManyDistributors,
+    #[msg("Reward type epoch budget exceeded")]
+    EpochBudgetExceeded,
+    #[msg("Recipient daily reward cap exceeded")]
+    RecipientDailyCapExceeded,
+    #[msg("Epoch duration must be positive and at most 30 days")]
+    InvalidEpochDuration,
+    #[msg("Reward type budget exceeds the maximum")]
+    ExcessiveRewardBudget,
+    #[msg("Recipient daily cap exceeds the maximum")]
+    ExcessiveRecipientCap,
+    #[msg("Withdrawal council is already set; propose a council update instead")]
+    CouncilAlreadySet,
+    #[msg("Council needs a guardian")]
+    InvalidGuardian,
+    #[msg("Proposal is not a council update")]
+    NotCouncilUpdate,
+    #[msg("Proposal is a council update, not a withdrawal")]
+    NotWithdrawal,
 }
@@ -3,10 +3,16 @@ use anchor_spl::{
     associated_token::AssociatedToken,
     token::{self, Mint, MintTo, Token, TokenAccount, Transfer, Burn},
 };
+use mafia_randomness::{seed_u64, CommitRevealSecrets, RandomnessRequest};
 // Simplified for MVP - metadata will be handled separately
 
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnT");
 
+const MAX_MINTERS: usize = 10;
+/// Loot drop weights per ItemRarity: 50% / 30% / 13% / 6% / 1%
+pub const WEAPON_DROP_WEIGHTS: [u16; 5] = [5000, 3000, 1300, 600, 100];
+const WEAPON_TYPE_COUNT: u64 = 8;
+
 #[program]
 pub mod item_vault {
     use super::*;
@@ -21,6 +27,8 @@ pub mod item_vault {
         config.total_vehicles_minted = 0;
         config.is_active = true;
         config.upgrade_fee_base = 100_000_000; // 0.1 MOB base upgrade fee
+        config.minters = vec![ctx.accounts.authority.key()];
+        config.vrf_oracle = Pubkey::default();
         
         msg!("Item vault program initialized");
         Ok(())
@@ -37,6 +45,10 @@ pub mod item_vault {
         let config = &mut ctx.accounts.config;
         
         require!(config.is_active, ErrorCode::ProgramInactive);
+        require!(
+            config.minters.contains(&ctx.accounts.minter.key()),
+            ErrorCode::UnauthorizedMinter
+        );
         require!(name.len() <= 32, ErrorCode::NameTooLong);
         require!(base_damage > 0 && base_damage <= 1000, ErrorCode::InvalidDamage);
         
@@ -54,6 +66,7 @@ pub mod item_vault {
         weapon.upgrade_level = 0;
         weapon.durability = 100;
         weapon.is_equipped = false;
+        weapon.equipped_to = Pubkey::default();
         weapon.created_at = Clock::get()?.unix_timestamp;
         
         // Metadata will be handled by external service for MVP
@@ -102,6 +115,10 @@ pub mod item_vault {
         let config = &mut ctx.accounts.config;
         
         require!(config.is_active, ErrorCode::ProgramInactive);
+        require!(
+            config.minters.contains(&ctx.accounts.minter.key()),
+            ErrorCode::UnauthorizedMinter
+        );
         require!(name.len() <= 32, ErrorCode::NameTooLong);
         require!(speed > 0 && speed <= 1000, ErrorCode::InvalidSpeed);
         require!(capacity > 0 && capacity <= 20, ErrorCode::InvalidCapacity);
@@ -152,6 +169,203 @@ pub mod item_vault {
         Ok(())
     }
 
+    /// Start a weapon loot drop for a player. The minter co-signs as the
+    /// server side of a commit-reveal, or binds a pending VRF request.
+    pub fn request_weapon_drop(
+        ctx: Context<RequestWeaponDrop>,
+        randomness: RandomnessRequest,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        
+        require!(config.is_active, ErrorCode::ProgramInactive);
+        require!(
+            config.minters.contains(&ctx.accounts.minter.key()),
+            ErrorCode::UnauthorizedMinter
+        );
+        match randomness {
+            RandomnessRequest::CommitReveal { .. } => {}
+            RandomnessRequest::Vrf { .. } => randomness.validate_vrf(
+                ctx.accounts.vrf_result.as_ref().map(|account| account.as_ref()),
+                &config.vrf_oracle,
+                &ctx.accounts.pending_drop.key(),
+            )?,
+            RandomnessRequest::Commit { .. } => return err!(ErrorCode::UnsupportedRandomness),
+        }
+        
+        let current_time = Clock::get()?.unix_timestamp;
+        let pending_drop = &mut ctx.accounts.pending_drop;
+        pending_drop.owner = ctx.accounts.owner.key();
+        pending_drop.minter = ctx.accounts.minter.key();
+        pending_drop.randomness = randomness;
+        pending_drop.requested_at = current_time;
+        
+        emit!(WeaponDropRequested {
+            owner: pending_drop.owner,
+            minter: pending_drop.minter,
+            randomness,
+            timestamp: current_time,
+        });
+        
+        msg!("Weapon drop requested for {}", pending_drop.owner);
+        Ok(())
+    }
+
+    /// Resolve a pending loot drop and mint the rolled weapon to the player
+    pub fn claim_weapon_drop(
+        ctx: Context<ClaimWeaponDrop>,
+        secrets: Option<CommitRevealSecrets>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let pending_drop = &ctx.accounts.pending_drop;
+        
+        require!(config.is_active, ErrorCode::ProgramInactive);
+        
+        let seed = pending_drop.randomness.resolve(
+            secrets.as_ref(),
+            ctx.accounts.vrf_result.as_ref().map(|account| account.as_ref()),
+            &config.vrf_oracle,
+            &[
+                pending_drop.key().as_ref(),
+                &pending_drop.requested_at.to_le_bytes(),
+            ],
+        )?;
+        let (weapon_type, rarity, base_damage) = roll_weapon(&seed);
+        validate_weapon_stats(base_damage, &rarity)?;
+        
+        let current_time = Clock::get()?.unix_timestamp;
+        let weapon = &mut ctx.accounts.weapon;
+        weapon.mint = ctx.accounts.mint.key();
+        weapon.owner = ctx.accounts.owner.key();
+        weapon.weapon_type = weapon_type;
+        weapon.rarity = rarity;
+        weapon.base_damage = base_damage;
+        weapon.current_damage = base_damage;
+        weapon.upgrade_level = 0;
+        weapon.durability = 100;
+        weapon.is_equipped = false;
+        weapon.equipped_to = Pubkey::default();
+        weapon.created_at = current_time;
+        
+        let seeds: &[&[u8]] = &[b"mint_authority", &[ctx.bumps.mint_authority]];
+        let signer = &[seeds];
+        
+        let cpi_accounts = MintTo {
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.token_account.to_account_info(),
+            authority: ctx.accounts.mint_authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        
+        token::mint_to(cpi_ctx, 1)?;
+        
+        config.total_weapons_minted += 1;
+        
+        emit!(WeaponMinted {
+            mint: weapon.mint,
+            owner: weapon.owner,
+            weapon_type,
+            rarity,
+            base_damage,
+            timestamp: current_time,
+        });
+        
+        emit!(WeaponDropRolled {
+            mint: weapon.mint,
+            owner: weapon.owner,
+            randomness: pending_drop.randomness,
+            seed,
+            weapon_type,
+            rarity,
+            base_damage,
+        });
+        
+        msg!("Weapon drop claimed: {} with {} damage", weapon_type as u8, base_damage);
+        Ok(())
+    }
+
+    /// Discard a drop whose randomness was never revealed (minter only).
+    /// The player forfeits it, so withholding a bad roll gains nothing.
+    pub fn cancel_weapon_drop(ctx: Context<CancelWeaponDrop>) -> Result<()> {
+        require!(
+            ctx.accounts.config.minters.contains(&ctx.accounts.minter.key()),
+            ErrorCode::UnauthorizedMinter
+        );
+        
+        emit!(WeaponDropCancelled {
+            owner: ctx.accounts.pending_drop.owner,
+            minter: ctx.accounts.minter.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Weapon drop cancelled for {}", ctx.accounts.pending_drop.owner);
+        Ok(())
+    }
+
+    /// Set the program that owns VRF result accounts for loot drops (admin only)
+    pub fn set_vrf_oracle(ctx: Context<ManageMinters>, vrf_oracle: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        
+        config.vrf_oracle = vrf_oracle;
+        
+        msg!("Loot drop VRF oracle set to {}", vrf_oracle);
+        Ok(())
+    }
+
+    /// Allow a key to mint weapons and vehicles (admin only)
+    pub fn add_minter(ctx: Context<ManageMinters>, minter: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(!config.minters.contains(&minter), ErrorCode::MinterAlreadyExists);
+        require!(config.minters.len() < MAX_MINTERS, ErrorCode::TooManyMinters);
+        
+        config.minters.push(minter);
+        
+        emit!(MinterAdded {
+            minter,
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Minter added: {}", minter);
+        Ok(())
+    }
+
+    /// Revoke a key's permission to mint items (admin only)
+    pub fn remove_minter(ctx: Context<ManageMinters>, minter: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        
+        let index = config
+            .minters
+            .iter()
+            .position(|key| *key == minter)
+            .ok_or(ErrorCode::MinterNotFound)?;
+        config.minters.remove(index);
+        
+        emit!(MinterRemoved {
+            minter,
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Minter removed: {}", minter);
+        Ok(())
+    }
+
     /// Upgrade weapon with MOB tokens
     pub fn upgrade_weapon(ctx: Context<UpgradeWeapon>) -> Result<()> {
         let config = &ctx.accounts.config;
@@ -201,8 +415,10 @@ pub mod item_vault {
         require!(weapon.owner == ctx.accounts.owner.key(), ErrorCode::NotOwner);
         require!(weapon.durability > 0, ErrorCode::WeaponBroken);
         require!(!weapon.is_equipped, ErrorCode::AlreadyEquipped);
+        require!(!ctx.accounts.character.is_staked, ErrorCode::CharacterStaked);
         
         weapon.is_equipped = true;
+        weapon.equipped_to = ctx.accounts.character.key();
         
         emit!(WeaponEquipped {
             mint: weapon.mint,
@@ -223,6 +439,7 @@ pub mod item_vault {
         require!(weapon.is_equipped, ErrorCode::NotEquipped);
         
         weapon.is_equipped = false;
+        weapon.equipped_to = Pubkey::default();
         
         emit!(WeaponUnequipped {
             mint: weapon.mint,
@@ -373,6 +590,9 @@ pub struct MintWeapon<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     
+    /// Game server, mission program PDA or admin allowed to mint
+    pub minter: Signer<'info>,
+    
     /// CHECK: Owner of the weapon
     pub owner: UncheckedAccount<'info>,
     
@@ -425,6 +645,9 @@ pub struct MintVehicle<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     
+    /// Game server, mission program PDA or admin allowed to mint
+    pub minter: Signer<'info>,
+    
     /// CHECK: Owner of the vehicle
     pub owner: UncheckedAccount<'info>,
     
@@ -433,6 +656,130 @@ pub struct MintVehicle<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct RequestWeaponDrop<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ItemConfig>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + PendingDrop::INIT_SPACE,
+        seeds = [b"weapon_drop", owner.key().as_ref()],
+        bump
+    )]
+    pub pending_drop: Account<'info, PendingDrop>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    /// Game server or mission program PDA granting the drop
+    pub minter: Signer<'info>,
+    
+    /// CHECK: Oracle result account for VRF drops, validated by mafia_randomness
+    pub vrf_result: Option<UncheckedAccount<'info>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimWeaponDrop<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ItemConfig>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"weapon_drop", owner.key().as_ref()],
+        bump
+    )]
+    pub pending_drop: Account<'info, PendingDrop>,
+    
+    #[account(
+        init,
+        payer = owner,
+        mint::decimals = 0,
+        mint::authority = mint_authority,
+    )]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(
+        seeds = [b"mint_authority"],
+        bump
+    )]
+    /// CHECK: PDA used as mint authority
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint,
+        associated_token::authority = owner,
+    )]
+    pub token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Weapon::INIT_SPACE,
+        seeds = [b"weapon", mint.key().as_ref()],
+        bump
+    )]
+    pub weapon: Account<'info, Weapon>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    /// CHECK: Oracle result account for VRF drops, validated by mafia_randomness
+    pub vrf_result: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelWeaponDrop<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ItemConfig>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"weapon_drop", owner.key().as_ref()],
+        bump
+    )]
+    pub pending_drop: Account<'info, PendingDrop>,
+    
+    /// CHECK: Player who paid for the pending drop, receives its rent
+    #[account(mut, address = pending_drop.owner)]
+    pub owner: UncheckedAccount<'info>,
+    
+    pub minter: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ManageMinters<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ItemConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct UpgradeWeapon<'info> {
     #[account(
@@ -471,8 +818,9 @@ pub struct EquipWeapon<'info> {
     )]
     pub weapon: Account<'info, Weapon>,
     
-    /// CHECK: Character account
-    pub character: UncheckedAccount<'info>,
+    /// Character to equip; must belong to the owner and not be staked
+    #[account(constraint = character.owner == owner.key() @ ErrorCode::NotOwner)]
+    pub character: Account<'info, character_nft::Character>,
     
     pub owner: Signer<'info>,
 }
@@ -486,7 +834,8 @@ pub struct UnequipWeapon<'info> {
     )]
     pub weapon: Account<'info, Weapon>,
     
-    /// CHECK: Character account
+    /// CHECK: Character the weapon is equipped to
+    #[account(address = weapon.equipped_to @ ErrorCode::NotEquippedToCharacter)]
     pub character: UncheckedAccount<'info>,
     
     pub owner: Signer<'info>,
@@ -563,6 +912,18 @@ pub struct ItemConfig {
     pub total_vehicles_minted: u32,
     pub is_active: bool,
     pub upgrade_fee_base: u64,
+    #[max_len(10)]
+    pub minters: Vec<Pubkey>,
+    pub vrf_oracle: Pubkey, // Program that owns VRF result accounts for loot drops
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PendingDrop {
+    pub owner: Pubkey,
+    pub minter: Pubkey,
+    pub randomness: RandomnessRequest,
+    pub requested_at: i64,
 }
 
 #[account]
@@ -577,6 +938,7 @@ pub struct Weapon {
     pub upgrade_level: u8,
     pub durability: u8,
     pub is_equipped: bool,
+    pub equipped_to: Pubkey,
     pub created_at: i64,
 }
 
@@ -627,14 +989,18 @@ pub enum ItemRarity {
 }
 
 // Helper functions
-fn validate_weapon_stats(damage: u16, rarity: &ItemRarity) -> Result<()> {
-    let (min_damage, max_damage) = match rarity {
+fn weapon_damage_range(rarity: &ItemRarity) -> (u16, u16) {
+    match rarity {
         ItemRarity::Common => (10, 25),
         ItemRarity::Uncommon => (26, 40),
         ItemRarity::Rare => (41, 60),
         ItemRarity::Epic => (61, 80),
         ItemRarity::Legendary => (81, 100),
-    };
+    }
+}
+
+fn validate_weapon_stats(damage: u16, rarity: &ItemRarity) -> Result<()> {
+    let (min_damage, max_damage) = weapon_damage_range(rarity);
     
     require!(
         damage >= min_damage && damage <= max_damage,
@@ -644,6 +1010,45 @@ fn validate_weapon_stats(damage: u16, rarity: &ItemRarity) -> Result<()> {
     Ok(())
 }
 
+/// Weapon type, rarity (by `WEAPON_DROP_WEIGHTS`) and damage within the
+/// rarity's range, each read from its own 8 bytes of `seed`
+fn roll_weapon(seed: &[u8; 32]) -> (WeaponType, ItemRarity, u16) {
+    let weapon_type = match seed_u64(seed, 0) % WEAPON_TYPE_COUNT {
+        0 => WeaponType::Pistol,
+        1 => WeaponType::Rifle,
+        2 => WeaponType::Shotgun,
+        3 => WeaponType::SMG,
+        4 => WeaponType::Sniper,
+        5 => WeaponType::Knife,
+        6 => WeaponType::Bat,
+        _ => WeaponType::Grenade,
+    };
+    
+    let total: u64 = WEAPON_DROP_WEIGHTS.iter().map(|w| *w as u64).sum();
+    let mut roll = seed_u64(seed, 8) % total;
+    let mut tier = WEAPON_DROP_WEIGHTS.len() - 1;
+    for (i, weight) in WEAPON_DROP_WEIGHTS.iter().enumerate() {
+        if roll < *weight as u64 {
+            tier = i;
+            break;
+        }
+        roll -= *weight as u64;
+    }
+    let rarity = match tier {
+        0 => ItemRarity::Common,
+        1 => ItemRarity::Uncommon,
+        2 => ItemRarity::Rare,
+        3 => ItemRarity::Epic,
+        _ => ItemRarity::Legendary,
+    };
+    
+    let (min_damage, max_damage) = weapon_damage_range(&rarity);
+    let spread = (max_damage - min_damage + 1) as u64;
+    let base_damage = min_damage + (seed_u64(seed, 16) % spread) as u16;
+    
+    (weapon_type, rarity, base_damage)
+}
+
 fn calculate_upgrade_cost(current_level: u8, rarity: &ItemRarity) -> u64 {
     let base_cost = 100_000_000; // 0.1 MOB
     let level_multiplier = (current_level as u64 + 1) * (current_level as u64 + 1);
@@ -693,6 +1098,33 @@ pub struct WeaponMinted {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct WeaponDropRequested {
+    pub owner: Pubkey,
+    pub minter: Pubkey,
+    pub randomness: RandomnessRequest,
+    pub timestamp: i64,
+}
+
+/// Everything needed to recompute a loot drop's rolls off-chain
+#[event]
+pub struct WeaponDropRolled {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub randomness: RandomnessRequest,
+    pub seed: [u8; 32],
+    pub weapon_type: WeaponType,
+    pub rarity: ItemRarity,
+    pub base_damage: u16,
+}
+
+#[event]
+pub struct WeaponDropCancelled {
+    pub owner: Pubkey,
+    pub minter: Pubkey,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct VehicleMinted {
     pub mint: Pubkey,
@@ -738,6 +1170,20 @@ pub struct WeaponRepaired {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct MinterAdded {
+    pub minter: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MinterRemoved {
+    pub minter: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct ItemTransferred {
     pub mint: Pubkey,
@@ -774,4 +1220,20 @@ pub enum ErrorCode {
     WeaponNotDamaged,
     #[msg("Cannot transfer equipped item")]
     CannotTransferEquipped,
+    #[msg("Unauthorized access")]
+    Unauthorized,
+    #[msg("Signer is not an authorized item minter")]
+    UnauthorizedMinter,
+    #[msg("Minter is already authorized")]
+    MinterAlreadyExists,
+    #[msg("Minter not found")]
+    MinterNotFound,
+    #[msg("Too many authorized minters")]
+    TooManyMinters,
+    #[msg("Cannot equip items to a staked character")]
+    CharacterStaked,
+    #[msg("Loot drops need a server commitment or a VRF result")]
+    UnsupportedRandomness,
+    #[msg("Weapon is not equipped to this character")]
+    NotEquippedToCharacter,
 }
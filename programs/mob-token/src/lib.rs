@@ -6,12 +6,30 @@ use anchor_spl::{
 
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnV");
 
+const MAX_ISSUERS: usize = 10;
+const REWARD_TYPE_COUNT: usize = 8;
+const EMISSION_WINDOW_HOURS: usize = 24; // Rolling emission window, in hourly buckets
+const SECONDS_PER_HOUR: i64 = 3600;
+/// Largest single Staking reward, also the per-claim cap for character staking yield
+pub const MAX_STAKING_REWARD: u64 = 10_000_000_000; // 10 MOB (daily)
+
 #[program]
 pub mod mob_token {
     use super::*;
 
-    /// Initialize the MOB token program
-    pub fn initialize(ctx: Context<Initialize>, daily_emission: u64) -> Result<()> {
+    /// Initialize the MOB token program with its starting reward issuers
+    /// (game backend key plus the turf_control, game_treasury and
+    /// character_nft signer PDAs)
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        daily_emission: u64,
+        issuers: Vec<Pubkey>,
+    ) -> Result<()> {
+        require!(issuers.len() <= MAX_ISSUERS, ErrorCode::TooManyIssuers);
+        for (i, issuer) in issuers.iter().enumerate() {
+            require!(!issuers[..i].contains(issuer), ErrorCode::IssuerAlreadyExists);
+        }
+        
         let config = &mut ctx.accounts.config;
         config.authority = ctx.accounts.authority.key();
         config.mint = ctx.accounts.mint.key();
@@ -21,6 +39,11 @@ pub mod mob_token {
         config.last_emission_time = Clock::get()?.unix_timestamp;
         config.is_active = true;
         config.anti_bot_threshold = 100; // Max 100 transactions per hour per wallet
+        config.issuers = issuers;
+        config.hourly_emitted = [0; EMISSION_WINDOW_HOURS];
+        config.reward_type_budgets = [0; REWARD_TYPE_COUNT];
+        config.hourly_type_emitted = [[0; REWARD_TYPE_COUNT]; EMISSION_WINDOW_HOURS];
+        config.governance = Pubkey::default();
         
         msg!("MOB token program initialized with daily emission: {}", daily_emission);
         Ok(())
@@ -35,19 +58,39 @@ pub mod mob_token {
         let config = &mut ctx.accounts.config;
         
         require!(config.is_active, ErrorCode::ProgramInactive);
+        require!(
+            config.issuers.contains(&ctx.accounts.issuer.key()),
+            ErrorCode::UnauthorizedIssuer
+        );
         
-        // Check daily emission limits
+        // Drop hourly buckets older than the rolling 24h window
         let current_time = Clock::get()?.unix_timestamp;
-        let time_diff = current_time - config.last_emission_time;
-        
-        if time_diff >= 86400 { // 24 hours
-            config.last_emission_time = current_time;
-            // Reset daily counters if needed
-        }
+        config.roll_emission_window(current_time);
         
         // Validate reward amount based on type
         validate_reward_amount(amount, &reward_type)?;
         
+        // Enforce the rolling emission cap and the per-type sub-budget
+        let emitted_in_window = config
+            .emitted_in_window()
+            .checked_add(amount)
+            .ok_or(ErrorCode::DailyEmissionExceeded)?;
+        require!(
+            emitted_in_window <= config.daily_emission,
+            ErrorCode::DailyEmissionExceeded
+        );
+        
+        let type_index = reward_type as usize;
+        let type_emitted_in_window = config
+            .type_emitted_in_window(type_index)
+            .checked_add(amount)
+            .ok_or(ErrorCode::RewardTypeBudgetExceeded)?;
+        let type_budget = config.reward_type_budgets[type_index];
+        require!(
+            type_budget == 0 || type_emitted_in_window <= type_budget,
+            ErrorCode::RewardTypeBudgetExceeded
+        );
+        
         // Anti-bot protection
         let player_data = &mut ctx.accounts.player_data;
         check_rate_limit(player_data, current_time)?;
@@ -70,12 +113,16 @@ pub mod mob_token {
         token::mint_to(cpi_ctx, amount)?;
         
         config.total_minted += amount;
+        let bucket = config.current_bucket();
+        config.hourly_emitted[bucket] += amount;
+        config.hourly_type_emitted[bucket][type_index] += amount;
         player_data.total_earned += amount;
         player_data.last_reward_time = current_time;
         player_data.transaction_count += 1;
         
         emit!(RewardMinted {
             recipient: ctx.accounts.recipient.key(),
+            issuer: ctx.accounts.issuer.key(),
             amount,
             reward_type,
             timestamp: current_time,
@@ -163,17 +210,70 @@ pub mod mob_token {
         Ok(())
     }
 
+    /// Authorize a reward issuer: game backend key or program PDA (admin only)
+    pub fn add_issuer(ctx: Context<ManageIssuers>, issuer: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(!config.issuers.contains(&issuer), ErrorCode::IssuerAlreadyExists);
+        require!(config.issuers.len() < MAX_ISSUERS, ErrorCode::TooManyIssuers);
+        
+        config.issuers.push(issuer);
+        
+        emit!(IssuerAdded {
+            issuer,
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Reward issuer added: {}", issuer);
+        Ok(())
+    }
+
+    /// Revoke a reward issuer (admin only)
+    pub fn remove_issuer(ctx: Context<ManageIssuers>, issuer: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        
+        require!(
+            ctx.accounts.authority.key() == config.authority,
+            ErrorCode::Unauthorized
+        );
+        
+        let index = config
+            .issuers
+            .iter()
+            .position(|key| *key == issuer)
+            .ok_or(ErrorCode::IssuerNotFound)?;
+        config.issuers.remove(index);
+        
+        emit!(IssuerRemoved {
+            issuer,
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Reward issuer removed: {}", issuer);
+        Ok(())
+    }
+
     /// Update program configuration (admin only)
     pub fn update_config(
         ctx: Context<UpdateConfig>,
         new_daily_emission: Option<u64>,
         new_anti_bot_threshold: Option<u32>,
         new_is_active: Option<bool>,
+        new_reward_type_budgets: Option<[u64; REWARD_TYPE_COUNT]>,
+        new_governance: Option<Pubkey>,
     ) -> Result<()> {
         let config = &mut ctx.accounts.config;
+        let authority = ctx.accounts.authority.key();
         
+        // The governance PDA may tune parameters; only the admin can re-point it
         require!(
-            ctx.accounts.authority.key() == config.authority,
+            authority == config.authority || authority == config.governance,
             ErrorCode::Unauthorized
         );
         
@@ -189,6 +289,19 @@ pub mod mob_token {
             config.is_active = active;
         }
         
+        if let Some(budgets) = new_reward_type_budgets {
+            require!(
+                budgets.iter().all(|budget| *budget <= config.daily_emission),
+                ErrorCode::InvalidRewardBudget
+            );
+            config.reward_type_budgets = budgets;
+        }
+        
+        if let Some(governance) = new_governance {
+            require!(authority == config.authority, ErrorCode::Unauthorized);
+            config.governance = governance;
+        }
+        
         msg!("MOB token config updated");
         Ok(())
     }
@@ -267,6 +380,9 @@ pub struct MintReward<'info> {
     /// CHECK: Recipient of the reward
     pub recipient: UncheckedAccount<'info>,
     
+    /// Authorized reward issuer (game backend or program PDA)
+    pub issuer: Signer<'info>,
+    
     #[account(mut)]
     pub payer: Signer<'info>,
     
@@ -335,6 +451,18 @@ pub struct TransferTokens<'info> {
     pub token_program: Program<'info, Token>,
 }
 
+#[derive(Accounts)]
+pub struct ManageIssuers<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, TokenConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct UpdateConfig<'info> {
     #[account(
@@ -358,6 +486,43 @@ pub struct TokenConfig {
     pub last_emission_time: i64,
     pub is_active: bool,
     pub anti_bot_threshold: u32,
+    #[max_len(10)]
+    pub issuers: Vec<Pubkey>,
+    /// MOB minted per hour over the rolling window, indexed by hour of day
+    pub hourly_emitted: [u64; 24],
+    /// Rolling 24h cap per RewardType, indexed by variant; 0 means no sub-budget
+    pub reward_type_budgets: [u64; 8],
+    pub hourly_type_emitted: [[u64; 8]; 24],
+    pub governance: Pubkey, // fam_token governance PDA, may update parameters
+}
+
+impl TokenConfig {
+    fn current_bucket(&self) -> usize {
+        (self.last_emission_time / SECONDS_PER_HOUR).rem_euclid(EMISSION_WINDOW_HOURS as i64) as usize
+    }
+
+    /// Clear the buckets of hours that have left the window since the last mint
+    pub fn roll_emission_window(&mut self, now: i64) {
+        let last_hour = self.last_emission_time / SECONDS_PER_HOUR;
+        let elapsed = (now / SECONDS_PER_HOUR - last_hour).clamp(0, EMISSION_WINDOW_HOURS as i64);
+        for offset in 1..=elapsed {
+            let bucket = (last_hour + offset).rem_euclid(EMISSION_WINDOW_HOURS as i64) as usize;
+            self.hourly_emitted[bucket] = 0;
+            self.hourly_type_emitted[bucket] = [0; REWARD_TYPE_COUNT];
+        }
+        self.last_emission_time = now;
+    }
+
+    pub fn emitted_in_window(&self) -> u64 {
+        self.hourly_emitted.iter().sum()
+    }
+
+    pub fn type_emitted_in_window(&self, type_index: usize) -> u64 {
+        self.hourly_type_emitted
+            .iter()
+            .map(|bucket| bucket[type_index])
+            .sum()
+    }
 }
 
 #[account]
@@ -403,7 +568,7 @@ fn validate_reward_amount(amount: u64, reward_type: &RewardType) -> Result<()> {
         RewardType::PvpVictory => 2_000_000_000, // 2 MOB
         RewardType::TerritoryDefense => 1_000_000_000, // 1 MOB
         RewardType::BusinessIncome => 500_000_000, // 0.5 MOB
-        RewardType::Staking => 10_000_000_000, // 10 MOB (daily)
+        RewardType::Staking => MAX_STAKING_REWARD,
         RewardType::Tournament => 10_000_000_000, // 10 MOB
         RewardType::Daily => 1_000_000_000, // 1 MOB
         RewardType::Achievement => 5_000_000_000, // 5 MOB
@@ -448,11 +613,26 @@ fn check_rate_limit(player_data: &mut PlayerData, current_time: i64) -> Result<(
 #[event]
 pub struct RewardMinted {
     pub recipient: Pubkey,
+    pub issuer: Pubkey,
     pub amount: u64,
     pub reward_type: RewardType,
     pub timestamp: i64,
 }
 
+#[event]
+pub struct IssuerAdded {
+    pub issuer: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct IssuerRemoved {
+    pub issuer: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct TokensBurned {
     pub user: Pubkey,
@@ -483,4 +663,18 @@ pub enum ErrorCode {
     ExcessiveBurnAmount,
     #[msg("Rate limit exceeded - too many transactions")]
     RateLimitExceeded,
+    #[msg("Signer is not an authorized reward issuer")]
+    UnauthorizedIssuer,
+    #[msg("Issuer is already authorized")]
+    IssuerAlreadyExists,
+    #[msg("Issuer not found")]
+    IssuerNotFound,
+    #[msg("Too many authorized issuers")]
+    TooManyIssuers,
+    #[msg("Daily emission budget exhausted")]
+    DailyEmissionExceeded,
+    #[msg("Daily budget for this reward type exhausted")]
+    RewardTypeBudgetExceeded,
+    #[msg("Reward type budget exceeds daily emission")]
+    InvalidRewardBudget,
 }
@@ -1,11 +1,14 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::sysvar::slot_hashes};
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, Mint, MintTo, Token, TokenAccount, Transfer},
+    token::{self, CloseAccount, Mint, MintTo, Token, TokenAccount, Transfer},
 };
+use mafia_randomness::{seed_u64, slot_hash, CommitRevealSecrets, RandomnessRequest};
 
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnU");
 
+const SEED_SLOT_DELAY: u64 = 2; // Slots between locking an attack's seed slot and its hash being usable
+
 #[program]
 pub mod turf_control {
     use super::*;
@@ -21,6 +24,10 @@ pub mod turf_control {
         config.base_income_rate = 10_000_000; // 0.01 MOB per day base
         config.tax_rate = 2000; // 20% tax to treasury
         config.attack_cooldown = 172800; // 48 hours
+        config.attack_window = 3600; // 1 hour before an attack can be resolved
+        config.game_server = ctx.accounts.authority.key();
+        config.vrf_oracle = Pubkey::default();
+        config.governance = Pubkey::default();
         config.is_active = true;
         
         msg!("Turf control program initialized");
@@ -56,6 +63,7 @@ pub mod turf_control {
         territory.attack_wins = 0;
         territory.is_under_attack = false;
         territory.businesses = Vec::new();
+        territory.is_escrowed = false;
         
         // Mint the territory NFT
         let seeds = &[
@@ -195,7 +203,10 @@ pub mod turf_control {
     }
 
     /// Attack another territory (PvP)
-    pub fn attack_territory(ctx: Context<AttackTerritory>) -> Result<()> {
+    pub fn attack_territory(
+        ctx: Context<AttackTerritory>,
+        randomness: RandomnessRequest,
+    ) -> Result<()> {
         let config = &ctx.accounts.config;
         let attacker_territory = &ctx.accounts.attacker_territory;
         let defender_territory = &mut ctx.accounts.defender_territory;
@@ -205,23 +216,47 @@ pub mod turf_control {
         require!(attacker_territory.owner == ctx.accounts.attacker.key(), ErrorCode::NotOwner);
         require!(attacker_territory.mint != defender_territory.mint, ErrorCode::CannotAttackSelf);
         require!(!defender_territory.is_under_attack, ErrorCode::TerritoryUnderAttack);
+        // Only escrowed territories can change hands without the defender's signature
+        require!(defender_territory.is_escrowed, ErrorCode::NotEscrowed);
         
         // Check attack cooldown
         let time_since_last_attack = current_time - attacker_territory.last_attack_time;
         require!(time_since_last_attack >= config.attack_cooldown, ErrorCode::AttackCooldownActive);
         
+        // The game server co-signs its commitment; VRF requests must be bound
+        // to this attack and still pending
+        match randomness {
+            RandomnessRequest::CommitReveal { .. } => require!(
+                ctx.accounts.game_server.is_some(),
+                ErrorCode::GameServerSignatureRequired
+            ),
+            RandomnessRequest::Vrf { .. } => randomness.validate_vrf(
+                ctx.accounts.vrf_result.as_ref().map(|account| account.as_ref()),
+                &config.vrf_oracle,
+                &ctx.accounts.pending_attack.key(),
+            )?,
+            RandomnessRequest::Commit { .. } => return err!(ErrorCode::UnsupportedRandomness),
+        }
+        
         // Mark territory as under attack
         defender_territory.is_under_attack = true;
         
-        // Calculate attack success based on security levels and randomness
+        // Snapshot both sides so the outcome can't be influenced after launch
         let attack_power = 100 - attacker_territory.security_level;
         let defense_power = defender_territory.security_level;
+        let resolve_after = current_time + config.attack_window;
         
-        // Simple deterministic "randomness" based on slot and accounts
-        let slot = Clock::get()?.slot;
-        let random_factor = (slot % 100) as u8;
-        
-        let attack_success = (attack_power + random_factor) > defense_power;
+        let pending_attack = &mut ctx.accounts.pending_attack;
+        pending_attack.attacker = ctx.accounts.attacker.key();
+        pending_attack.attacker_territory = attacker_territory.mint;
+        pending_attack.defender = defender_territory.owner;
+        pending_attack.defender_territory = defender_territory.mint;
+        pending_attack.attack_power = attack_power;
+        pending_attack.defense_power = defense_power;
+        pending_attack.randomness = randomness;
+        pending_attack.launched_at = current_time;
+        pending_attack.resolve_after = resolve_after;
+        pending_attack.seed_slot = 0;
         
         emit!(TerritoryAttacked {
             attacker: ctx.accounts.attacker.key(),
@@ -229,60 +264,185 @@ pub mod turf_control {
             defender_territory: defender_territory.mint,
             attack_power,
             defense_power,
-            success: attack_success,
+            randomness,
+            resolve_after,
             timestamp: current_time,
         });
         
-        if attack_success {
-            msg!("Attack successful! Territory captured.");
-        } else {
-            msg!("Attack failed! Territory defended.");
+        msg!("Attack launched, resolvable after {}", resolve_after);
+        Ok(())
+    }
+
+    /// Pick the slot whose hash seeds the battle, once the attack window has
+    /// elapsed, so no one knows it at launch (callable by anyone). A slot
+    /// whose hash aged out of SlotHashes, or was skipped, can be re-picked.
+    pub fn lock_attack_seed(ctx: Context<LockAttackSeed>) -> Result<()> {
+        let pending_attack = &mut ctx.accounts.pending_attack;
+        let clock = Clock::get()?;
+        
+        require!(
+            clock.unix_timestamp >= pending_attack.resolve_after,
+            ErrorCode::AttackWindowActive
+        );
+        if pending_attack.seed_slot != 0 {
+            require!(
+                clock.slot > pending_attack.seed_slot
+                    && slot_hash(&ctx.accounts.slot_hashes, pending_attack.seed_slot)?.is_none(),
+                ErrorCode::SeedSlotAlreadyLocked
+            );
         }
         
+        pending_attack.seed_slot = clock.slot + SEED_SLOT_DELAY;
+        
+        emit!(AttackSeedLocked {
+            territory: pending_attack.defender_territory,
+            seed_slot: pending_attack.seed_slot,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        msg!("Attack seed slot locked at {}", pending_attack.seed_slot);
         Ok(())
     }
 
-    /// Resolve territory attack (called after attack period)
-    pub fn resolve_attack(ctx: Context<ResolveAttack>, attack_successful: bool) -> Result<()> {
+    /// Resolve territory attack once its seed slot has passed (callable by anyone)
+    pub fn resolve_attack(
+        ctx: Context<ResolveAttack>,
+        secrets: Option<CommitRevealSecrets>,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let pending_attack = &ctx.accounts.pending_attack;
         let attacker_territory = &mut ctx.accounts.attacker_territory;
         let defender_territory = &mut ctx.accounts.defender_territory;
+        let clock = Clock::get()?;
+        let current_time = clock.unix_timestamp;
         
         require!(defender_territory.is_under_attack, ErrorCode::NoActiveAttack);
+        require!(pending_attack.seed_slot != 0, ErrorCode::SeedSlotNotLocked);
+        require!(clock.slot > pending_attack.seed_slot, ErrorCode::SeedSlotPending);
+        
+        // Randomness still withheld a full window after the attack became
+        // resolvable forfeits it; a bad reveal or an expired seed slot never does
+        let vrf_result = ctx.accounts.vrf_result.as_ref().map(|account| account.as_ref());
+        let timed_out = current_time >= pending_attack.resolve_after + config.attack_window;
+        let seed = if timed_out
+            && pending_attack
+                .randomness
+                .is_pending(secrets.as_ref(), vrf_result, &config.vrf_oracle)?
+        {
+            None
+        } else {
+            let seed_slot_hash = slot_hash(&ctx.accounts.slot_hashes, pending_attack.seed_slot)?
+                .ok_or(ErrorCode::SeedSlotExpired)?;
+            Some(pending_attack.randomness.resolve(
+                secrets.as_ref(),
+                vrf_result,
+                &config.vrf_oracle,
+                &[
+                    pending_attack.key().as_ref(),
+                    &pending_attack.launched_at.to_le_bytes(),
+                    &seed_slot_hash,
+                ],
+            )?)
+        };
+        let attack_successful = seed.is_some_and(|seed| pending_attack.is_successful(&seed));
         
         if attack_successful {
-            // Transfer territory ownership
-            defender_territory.owner = attacker_territory.owner;
+            // Transfer territory ownership; the escrowed NFT stays in the
+            // vault and becomes withdrawable by the attacker
+            defender_territory.owner = pending_attack.attacker;
             attacker_territory.attack_wins += 1;
-            
-            // Transfer the NFT
-            let cpi_accounts = Transfer {
-                from: ctx.accounts.defender_token_account.to_account_info(),
-                to: ctx.accounts.attacker_token_account.to_account_info(),
-                authority: ctx.accounts.defender.to_account_info(),
-            };
-            let cpi_program = ctx.accounts.token_program.to_account_info();
-            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-            
-            token::transfer(cpi_ctx, 1)?;
         } else {
             defender_territory.defense_wins += 1;
         }
         
         defender_territory.is_under_attack = false;
-        attacker_territory.last_attack_time = Clock::get()?.unix_timestamp;
+        attacker_territory.last_attack_time = current_time;
         
         emit!(AttackResolved {
-            attacker: attacker_territory.owner,
-            defender: ctx.accounts.defender.key(),
+            attacker: pending_attack.attacker,
+            defender: pending_attack.defender,
             territory: defender_territory.mint,
             successful: attack_successful,
-            timestamp: Clock::get()?.unix_timestamp,
+            seed,
+            timestamp: current_time,
         });
         
         msg!("Attack resolved: {}", if attack_successful { "Successful" } else { "Failed" });
         Ok(())
     }
 
+    /// Move a territory NFT into the program vault so captures need no defender signature
+    pub fn deposit_territory(ctx: Context<DepositTerritory>) -> Result<()> {
+        let territory = &mut ctx.accounts.territory;
+        
+        require!(territory.owner == ctx.accounts.owner.key(), ErrorCode::NotOwner);
+        require!(!territory.is_under_attack, ErrorCode::TerritoryUnderAttack);
+        
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.owner_token_account.to_account_info(),
+            to: ctx.accounts.vault_token_account.to_account_info(),
+            authority: ctx.accounts.owner.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        
+        token::transfer(cpi_ctx, 1)?;
+        
+        territory.is_escrowed = true;
+        
+        emit!(TerritoryDeposited {
+            territory: territory.mint,
+            owner: ctx.accounts.owner.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Territory {} deposited into escrow", territory.mint);
+        Ok(())
+    }
+
+    /// Take a territory NFT out of the program vault back to its recorded owner
+    pub fn withdraw_territory(ctx: Context<WithdrawTerritory>) -> Result<()> {
+        let territory = &mut ctx.accounts.territory;
+        
+        require!(territory.owner == ctx.accounts.owner.key(), ErrorCode::NotOwner);
+        require!(!territory.is_under_attack, ErrorCode::TerritoryUnderAttack);
+        require!(territory.is_escrowed, ErrorCode::NotEscrowed);
+        
+        let seeds: &[&[u8]] = &[b"vault_authority", &[ctx.bumps.vault_authority]];
+        let signer = &[seeds];
+        
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault_token_account.to_account_info(),
+            to: ctx.accounts.owner_token_account.to_account_info(),
+            authority: ctx.accounts.vault_authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer);
+        
+        token::transfer(cpi_ctx, 1)?;
+        
+        // Return the vault's rent to the owner
+        let cpi_accounts_close = CloseAccount {
+            account: ctx.accounts.vault_token_account.to_account_info(),
+            destination: ctx.accounts.owner.to_account_info(),
+            authority: ctx.accounts.vault_authority.to_account_info(),
+        };
+        let cpi_ctx_close = CpiContext::new_with_signer(cpi_program, cpi_accounts_close, signer);
+        
+        token::close_account(cpi_ctx_close)?;
+        
+        territory.is_escrowed = false;
+        
+        emit!(TerritoryWithdrawn {
+            territory: territory.mint,
+            owner: ctx.accounts.owner.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Territory {} withdrawn from escrow", territory.mint);
+        Ok(())
+    }
+
     /// Add business to territory (increases income)
     pub fn add_business(
         ctx: Context<AddBusiness>,
@@ -340,6 +500,56 @@ pub mod turf_control {
         msg!("Business added: {:?}, Income boost: {}", business_type, income_increase);
         Ok(())
     }
+
+    /// Update program configuration (admin only)
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        new_attack_cooldown: Option<i64>,
+        new_attack_window: Option<i64>,
+        new_game_server: Option<Pubkey>,
+        new_vrf_oracle: Option<Pubkey>,
+        new_is_active: Option<bool>,
+        new_governance: Option<Pubkey>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let authority = ctx.accounts.authority.key();
+        
+        // The governance PDA may tune parameters; only the admin can re-point it
+        require!(
+            authority == config.authority || authority == config.governance,
+            ErrorCode::Unauthorized
+        );
+        
+        if let Some(cooldown) = new_attack_cooldown {
+            require!(cooldown >= 0, ErrorCode::InvalidAttackCooldown);
+            config.attack_cooldown = cooldown;
+        }
+        
+        if let Some(window) = new_attack_window {
+            require!(window > 0, ErrorCode::InvalidAttackWindow);
+            config.attack_window = window;
+        }
+        
+        if let Some(game_server) = new_game_server {
+            config.game_server = game_server;
+        }
+        
+        if let Some(vrf_oracle) = new_vrf_oracle {
+            config.vrf_oracle = vrf_oracle;
+        }
+        
+        if let Some(active) = new_is_active {
+            config.is_active = active;
+        }
+        
+        if let Some(governance) = new_governance {
+            require!(authority == config.authority, ErrorCode::Unauthorized);
+            config.governance = governance;
+        }
+        
+        msg!("Turf control config updated");
+        Ok(())
+    }
 }
 
 // Account structures
@@ -519,13 +729,47 @@ pub struct AttackTerritory<'info> {
     )]
     pub defender_territory: Account<'info, Territory>,
     
+    #[account(
+        init,
+        payer = attacker,
+        space = 8 + PendingAttack::INIT_SPACE,
+        seeds = [b"attack", defender_territory.mint.as_ref()],
+        bump
+    )]
+    pub pending_attack: Account<'info, PendingAttack>,
+    
+    #[account(mut)]
     pub attacker: Signer<'info>,
+    
+    /// Required for commit-reveal attacks
+    #[account(address = config.game_server)]
+    pub game_server: Option<Signer<'info>>,
+    
+    /// CHECK: Oracle result account for VRF attacks, validated by mafia_randomness
+    pub vrf_result: Option<UncheckedAccount<'info>>,
+    
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 pub struct ResolveAttack<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, TurfConfig>,
+    
     #[account(
         mut,
+        close = attacker,
+        seeds = [b"attack", defender_territory.mint.as_ref()],
+        bump
+    )]
+    pub pending_attack: Account<'info, PendingAttack>,
+    
+    #[account(
+        mut,
+        constraint = attacker_territory.mint == pending_attack.attacker_territory,
         seeds = [b"territory", attacker_territory.mint.as_ref()],
         bump
     )]
@@ -538,22 +782,116 @@ pub struct ResolveAttack<'info> {
     )]
     pub defender_territory: Account<'info, Territory>,
     
+    /// CHECK: Attacker who paid for the pending attack record, receives its rent
+    #[account(mut, address = pending_attack.attacker)]
+    pub attacker: UncheckedAccount<'info>,
+    
+    /// CHECK: Oracle result account for VRF attacks, validated by mafia_randomness
+    pub vrf_result: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: SlotHashes sysvar, parsed by mafia_randomness
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct LockAttackSeed<'info> {
     #[account(
         mut,
-        associated_token::mint = defender_territory.mint,
-        associated_token::authority = defender,
+        seeds = [b"attack", pending_attack.defender_territory.as_ref()],
+        bump
     )]
-    pub defender_token_account: Account<'info, TokenAccount>,
+    pub pending_attack: Account<'info, PendingAttack>,
     
+    /// CHECK: SlotHashes sysvar, parsed by mafia_randomness
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DepositTerritory<'info> {
     #[account(
         mut,
-        associated_token::mint = defender_territory.mint,
-        associated_token::authority = attacker_territory.owner,
+        seeds = [b"territory", territory.mint.as_ref()],
+        bump
+    )]
+    pub territory: Account<'info, Territory>,
+    
+    #[account(address = territory.mint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = owner,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        init,
+        payer = owner,
+        token::mint = mint,
+        token::authority = vault_authority,
+        seeds = [b"territory_vault", mint.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"vault_authority"],
+        bump
     )]
-    pub attacker_token_account: Account<'info, TokenAccount>,
+    /// CHECK: PDA that owns escrowed territory NFTs
+    pub vault_authority: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
     
-    pub defender: Signer<'info>,
     pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTerritory<'info> {
+    #[account(
+        mut,
+        seeds = [b"territory", territory.mint.as_ref()],
+        bump
+    )]
+    pub territory: Account<'info, Territory>,
+    
+    #[account(address = territory.mint)]
+    pub mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"territory_vault", mint.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"vault_authority"],
+        bump
+    )]
+    /// CHECK: PDA that owns escrowed territory NFTs
+    pub vault_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint,
+        associated_token::authority = owner,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
@@ -579,6 +917,18 @@ pub struct AddBusiness<'info> {
     pub token_program: Program<'info, Token>,
 }
 
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, TurfConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
 // Data structures
 #[account]
 #[derive(InitSpace)]
@@ -592,6 +942,10 @@ pub struct TurfConfig {
     pub tax_rate: u16, // Basis points
     pub attack_cooldown: i64, // Seconds
     pub is_active: bool,
+    pub attack_window: i64, // Seconds between launching and resolving an attack
+    pub game_server: Pubkey, // Co-signs commit-reveal attacks
+    pub vrf_oracle: Pubkey, // Program that owns VRF result accounts
+    pub governance: Pubkey, // fam_token governance PDA, may update parameters
 }
 
 #[account]
@@ -611,6 +965,29 @@ pub struct Territory {
     pub is_under_attack: bool,
     #[max_len(5)]
     pub businesses: Vec<Business>,
+    pub is_escrowed: bool,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PendingAttack {
+    pub attacker: Pubkey,
+    pub attacker_territory: Pubkey,
+    pub defender: Pubkey,
+    pub defender_territory: Pubkey,
+    pub attack_power: u8,
+    pub defense_power: u8,
+    pub randomness: RandomnessRequest,
+    pub launched_at: i64,
+    pub resolve_after: i64,
+    pub seed_slot: u64, // Slot whose hash seeds the battle; 0 until locked
+}
+
+impl PendingAttack {
+    pub fn is_successful(&self, seed: &[u8; 32]) -> bool {
+        let random_factor = (seed_u64(seed, 0) % 100) as u16;
+        self.attack_power as u16 + random_factor > self.defense_power as u16
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
@@ -676,7 +1053,15 @@ pub struct TerritoryAttacked {
     pub defender_territory: Pubkey,
     pub attack_power: u8,
     pub defense_power: u8,
-    pub success: bool,
+    pub randomness: RandomnessRequest,
+    pub resolve_after: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AttackSeedLocked {
+    pub territory: Pubkey,
+    pub seed_slot: u64,
     pub timestamp: i64,
 }
 
@@ -686,6 +1071,21 @@ pub struct AttackResolved {
     pub defender: Pubkey,
     pub territory: Pubkey,
     pub successful: bool,
+    pub seed: Option<[u8; 32]>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TerritoryDeposited {
+    pub territory: Pubkey,
+    pub owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TerritoryWithdrawn {
+    pub territory: Pubkey,
+    pub owner: Pubkey,
     pub timestamp: i64,
 }
 
@@ -727,4 +1127,26 @@ pub enum ErrorCode {
     NoActiveAttack,
     #[msg("Maximum businesses reached")]
     MaxBusinessesReached,
+    #[msg("Attack window has not elapsed yet")]
+    AttackWindowActive,
+    #[msg("Commit-reveal attacks must be co-signed by the game server")]
+    GameServerSignatureRequired,
+    #[msg("Unauthorized access")]
+    Unauthorized,
+    #[msg("Attack window must be positive")]
+    InvalidAttackWindow,
+    #[msg("Attack cooldown cannot be negative")]
+    InvalidAttackCooldown,
+    #[msg("Territory is not in escrow")]
+    NotEscrowed,
+    #[msg("Attack seed slot has already been locked")]
+    SeedSlotAlreadyLocked,
+    #[msg("Attack seed slot has not been locked yet")]
+    SeedSlotNotLocked,
+    #[msg("Attack seed slot has not passed yet")]
+    SeedSlotPending,
+    #[msg("Attacks need a server commitment or a VRF result")]
+    UnsupportedRandomness,
+    #[msg("Attack seed slot hash is no longer available, lock a new seed slot")]
+    SeedSlotExpired,
 }
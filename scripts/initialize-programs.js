@@ -35,7 +35,9 @@ const CONFIG = {
     symbol: 'MOB',
     decimals: 9,
     initialSupply: 0, // Mint on demand
-    maxTransactionsPerHour: 100
+    maxTransactionsPerHour: 100,
+    // Game backend key allowed to mint rewards; defaults to the deploy wallet
+    backendIssuer: process.env.MOB_BACKEND_ISSUER
   },
   
   famToken: {
@@ -128,7 +130,7 @@ class ProgramInitializer {
     return solBalance;
   }
   
-  async initializeCharacterNft() {
+  async initializeCharacterNft(mobMint) {
     console.log('\n🎭 Initializing Character NFT Program...');
     
     try {
@@ -138,6 +140,7 @@ class ProgramInitializer {
       // This would be the actual initialization call
       // For now, we'll simulate it
       console.log(`  📋 Collection Mint: ${collectionMint.publicKey.toString()}`);
+      console.log(`  💰 MOB Mint: ${mobMint.toString()}`);
       console.log(`  📊 Max Supply: ${CONFIG.characterNft.maxSupply.toLocaleString()}`);
       console.log(`  🔗 Base URI: ${CONFIG.characterNft.baseUri}`);
       console.log(`  💎 Royalty: ${CONFIG.characterNft.royaltyBasisPoints / 100}%`);
@@ -156,18 +159,33 @@ class ProgramInitializer {
     }
   }
   
+  mobRewardIssuers() {
+    // Backend key plus the program PDAs that sign mint_reward CPIs
+    const derive = (seed, programId) =>
+      PublicKey.findProgramAddressSync([Buffer.from(seed)], new PublicKey(programId))[0];
+    
+    return [
+      new PublicKey(CONFIG.mobToken.backendIssuer || this.wallet.publicKey),
+      derive('income_authority', this.programIds.TURF_CONTROL_PROGRAM_ID),
+      derive('treasury_authority', this.programIds.GAME_TREASURY_PROGRAM_ID),
+      derive('staking_authority', this.programIds.CHARACTER_NFT_PROGRAM_ID),
+    ];
+  }
+  
   async initializeMobToken() {
     console.log('\n🪙 Initializing MOB Token Program...');
     
     try {
       // Create MOB token mint
       const mobMint = Keypair.generate();
+      const issuers = this.mobRewardIssuers();
       
       console.log(`  📋 MOB Mint: ${mobMint.publicKey.toString()}`);
       console.log(`  🏷️  Name: ${CONFIG.mobToken.name}`);
       console.log(`  🔤 Symbol: ${CONFIG.mobToken.symbol}`);
       console.log(`  📊 Decimals: ${CONFIG.mobToken.decimals}`);
       console.log(`  ⏱️  Rate Limit: ${CONFIG.mobToken.maxTransactionsPerHour}/hour`);
+      issuers.forEach(issuer => console.log(`  🔑 Reward Issuer: ${issuer.toString()}`));
       
       // Save MOB mint for later use
       fs.writeFileSync('mob-token-mint.json', JSON.stringify({
@@ -367,8 +385,8 @@ anchor run test-character-mint
       await this.checkBalance();
       
       // Initialize all programs
-      const characterCollection = await this.initializeCharacterNft();
       const mobMint = await this.initializeMobToken();
+      const characterCollection = await this.initializeCharacterNft(mobMint);
       const famMint = await this.initializeFamToken();
       const itemCollections = await this.initializeItemVault(mobMint);
       const turfCollection = await this.initializeTurfControl(mobMint);
@@ -0,0 +1,1101 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { FamToken } from "../target/types/fam_token";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey, Transaction } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  transfer,
+} from "@solana/spl-token";
+import { expect } from "chai";
+
+describe("FAM Token Program", () => {
+  // Configure the client to use the local cluster
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.FamToken as Program<FamToken>;
+
+  // Test accounts
+  let authority: Keypair;
+  let mint: Keypair;
+  let configPda: PublicKey;
+  let mintAuthorityPda: PublicKey;
+  let stakeAuthorityPda: PublicKey;
+  let rewardVaultPda: PublicKey;
+
+  const FAM = 1_000_000;
+  const MIN_LOCK_DURATION = 604800;
+  const MAX_LOCK_DURATION = 63072000;
+
+  const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+  const stakePda = (user: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("stake"), user.toBuffer()],
+      program.programId
+    )[0];
+
+  const votePda = (proposal: PublicKey, voter: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("vote"), proposal.toBuffer(), voter.toBuffer()],
+      program.programId
+    )[0];
+
+  const fundedKeypair = async () => {
+    const keypair = Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(keypair.publicKey, 2 * LAMPORTS_PER_SOL)
+    );
+    return keypair;
+  };
+
+  const distribute = async (
+    recipient: PublicKey,
+    amount: number,
+    distributionType: object = { publicSale: {} }
+  ) => {
+    await program.methods
+      .distributeTokens(new anchor.BN(amount), distributionType as any)
+      .accounts({
+        config: configPda,
+        mintAuthority: mintAuthorityPda,
+        mint: mint.publicKey,
+        recipientTokenAccount: getAssociatedTokenAddressSync(mint.publicKey, recipient),
+        recipient,
+        authority: authority.publicKey,
+        payer: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+  };
+
+  const stakeTokens = (user: Keypair, amount: number, lockDuration = MIN_LOCK_DURATION) =>
+    program.methods
+      .stakeTokens(new anchor.BN(amount), new anchor.BN(lockDuration))
+      .accounts({
+        config: configPda,
+        rewardVault: rewardVaultPda,
+        stakeAccount: stakePda(user.publicKey),
+        stakeTokenAccount: getAssociatedTokenAddressSync(mint.publicKey, stakeAuthorityPda, true),
+        stakeAuthority: stakeAuthorityPda,
+        userTokenAccount: getAssociatedTokenAddressSync(mint.publicKey, user.publicKey),
+        mint: mint.publicKey,
+        user: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user]);
+
+  const stake = async (user: Keypair, amount: number, lockDuration = MIN_LOCK_DURATION) => {
+    await stakeTokens(user, amount, lockDuration).rpc();
+  };
+
+  const fundedStaker = async (amount: number) => {
+    const user = await fundedKeypair();
+    await distribute(user.publicKey, amount);
+    await stake(user, amount);
+    return user;
+  };
+
+  const clusterTime = async () => {
+    const blockTime = await provider.connection.getBlockTime(await provider.connection.getSlot());
+    return blockTime ?? Math.floor(Date.now() / 1000);
+  };
+
+  // The proposal PDA is seeded by the on-chain clock, so derive it from the
+  // cluster's block time and retry across a second boundary
+  const createProposal = async (
+    proposer: Keypair,
+    proposalType: object = { featureDevelopment: {} },
+    action: object | null = null
+  ) => {
+    for (let attempt = 0; attempt < 5; attempt++) {
+      const now = await clusterTime();
+      for (const timestamp of [now, now + 1]) {
+        const [proposalPda] = PublicKey.findProgramAddressSync(
+          [
+            Buffer.from("proposal"),
+            proposer.publicKey.toBuffer(),
+            new anchor.BN(timestamp).toArrayLike(Buffer, "le", 8),
+          ],
+          program.programId
+        );
+        try {
+          await program.methods
+            .createProposal("Raise mission rewards", "Snapshot voting test", proposalType as any, action as any)
+            .accounts({
+              config: configPda,
+              stakeAccount: stakePda(proposer.publicKey),
+              proposal: proposalPda,
+              proposer: proposer.publicKey,
+              systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([proposer])
+            .rpc();
+          return proposalPda;
+        } catch (error) {
+          if (!error.message.includes("ConstraintSeeds")) {
+            throw error;
+          }
+        }
+      }
+    }
+    throw new Error("Could not match the proposal seed to the cluster clock");
+  };
+
+  const vote = (
+    proposal: PublicKey,
+    voter: Keypair,
+    stakeAccount: PublicKey | null,
+    delegatePower: PublicKey | null = null
+  ) =>
+    program.methods
+      .voteOnProposal({ yes: {} })
+      .accounts({
+        proposal,
+        stakeAccount,
+        delegatePower,
+        voteRecord: votePda(proposal, voter.publicKey),
+        voter: voter.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([voter])
+      .rpc();
+
+  before(async () => {
+    authority = await fundedKeypair();
+    mint = Keypair.generate();
+
+    [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      program.programId
+    );
+    [mintAuthorityPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_authority")],
+      program.programId
+    );
+    [stakeAuthorityPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("stake_authority")],
+      program.programId
+    );
+    [rewardVaultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("reward_vault")],
+      program.programId
+    );
+  });
+
+  describe("Initialization", () => {
+    it("Should initialize the FAM token", async () => {
+      await program.methods
+        .initialize()
+        .accounts({
+          config: configPda,
+          mintAuthority: mintAuthorityPda,
+          mint: mint.publicKey,
+          rewardVault: rewardVaultPda,
+          stakeAuthority: stakeAuthorityPda,
+          authority: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([mint, authority])
+        .rpc();
+
+      const config = await program.account.tokenConfig.fetch(configPda);
+      expect(config.authority.toString()).to.equal(authority.publicKey.toString());
+      expect(config.totalStaked.toNumber()).to.equal(0);
+    });
+  });
+
+  describe("Voting Snapshots", () => {
+    let proposer: Keypair;
+    let proposal: PublicKey;
+
+    before(async () => {
+      proposer = await fundedStaker(20 * FAM);
+      // Checkpoints only count when recorded strictly before the proposal
+      await sleep(2000);
+      proposal = await createProposal(proposer);
+    });
+
+    it("Should count stake held before the proposal was created", async () => {
+      await vote(proposal, proposer, stakePda(proposer.publicKey));
+
+      const voteRecord = await program.account.voteRecord.fetch(votePda(proposal, proposer.publicKey));
+      expect(voteRecord.votingPower.toNumber()).to.be.at.least(20 * FAM);
+
+      const proposalAccount = await program.account.proposal.fetch(proposal);
+      expect(proposalAccount.yesVotes.toString()).to.equal(voteRecord.votingPower.toString());
+    });
+
+    it("Should not count stake added after the proposal was created", async () => {
+      const lateStaker = await fundedStaker(20 * FAM);
+
+      try {
+        await vote(proposal, lateStaker, stakePda(lateStaker.publicKey));
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NoVotingPower");
+      }
+    });
+  });
+
+  describe("Vote Delegation", () => {
+    let delegate: Keypair;
+    let delegator: Keypair;
+    let lateDelegator: Keypair;
+    let proposer: Keypair;
+    let delegatePowerPda: PublicKey;
+
+    const delegationAccounts = (user: Keypair) => ({
+      stakeAccount: stakePda(user.publicKey),
+      delegatePower: delegatePowerPda,
+      user: user.publicKey,
+    });
+
+    before(async () => {
+      delegate = await fundedKeypair();
+      delegator = await fundedStaker(20 * FAM);
+      lateDelegator = await fundedStaker(5 * FAM);
+      proposer = await fundedStaker(10 * FAM);
+
+      [delegatePowerPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("delegate_power"), delegate.publicKey.toBuffer()],
+        program.programId
+      );
+    });
+
+    it("Should register a delegate", async () => {
+      await program.methods
+        .registerDelegate()
+        .accounts({
+          delegatePower: delegatePowerPda,
+          delegate: delegate.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([delegate])
+        .rpc();
+
+      const delegatePower = await program.account.delegatePower.fetch(delegatePowerPda);
+      expect(delegatePower.delegate.toString()).to.equal(delegate.publicKey.toString());
+      expect(delegatePower.delegatedAmount.toNumber()).to.equal(0);
+    });
+
+    it("Should move delegated stake into the delegate's account", async () => {
+      await program.methods
+        .delegateVotes()
+        .accounts(delegationAccounts(delegator))
+        .signers([delegator])
+        .rpc();
+
+      const delegatePower = await program.account.delegatePower.fetch(delegatePowerPda);
+      expect(delegatePower.delegatedAmount.toNumber()).to.equal(20 * FAM);
+      expect(delegatePower.delegatorCount).to.equal(1);
+
+      const stakeAccount = await program.account.stakeAccount.fetch(stakePda(delegator.publicKey));
+      expect(stakeAccount.delegate.toString()).to.equal(delegate.publicKey.toString());
+    });
+
+    it("Should not stake more while delegated", async () => {
+      await distribute(delegator.publicKey, FAM);
+
+      try {
+        await stake(delegator, FAM);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("DelegationActive");
+      }
+    });
+
+    it("Should vote delegated power from the snapshot before the proposal", async () => {
+      await sleep(2000);
+      const proposal = await createProposal(proposer);
+
+      // Delegated after creation, so it stays with its owner on this proposal
+      await program.methods
+        .delegateVotes()
+        .accounts(delegationAccounts(lateDelegator))
+        .signers([lateDelegator])
+        .rpc();
+
+      await vote(proposal, delegate, null, delegatePowerPda);
+      const voteRecord = await program.account.voteRecord.fetch(votePda(proposal, delegate.publicKey));
+      expect(voteRecord.votingPower.toNumber()).to.equal(20 * FAM);
+
+      // Delegating gives up the lock boost the owner would have voted with
+      const { lockEnd } = await program.account.stakeAccount.fetch(stakePda(delegator.publicKey));
+      const { createdAt } = await program.account.proposal.fetch(proposal);
+      const remaining = lockEnd.toNumber() - createdAt.toNumber();
+      const boostedPower = Math.floor((20 * FAM * (10000 + (30000 * remaining) / MAX_LOCK_DURATION)) / 10000);
+      expect(remaining).to.be.greaterThan(0);
+      expect(voteRecord.votingPower.toNumber()).to.be.lessThan(boostedPower);
+
+      try {
+        await vote(proposal, delegator, stakePda(delegator.publicKey));
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NoVotingPower");
+      }
+
+      await vote(proposal, lateDelegator, stakePda(lateDelegator.publicKey));
+      const lateRecord = await program.account.voteRecord.fetch(votePda(proposal, lateDelegator.publicKey));
+      expect(lateRecord.votingPower.toNumber()).to.be.at.least(5 * FAM);
+    });
+
+    it("Should return power to the delegator on revocation", async () => {
+      await program.methods
+        .revokeDelegation()
+        .accounts(delegationAccounts(delegator))
+        .signers([delegator])
+        .rpc();
+
+      const delegatePower = await program.account.delegatePower.fetch(delegatePowerPda);
+      expect(delegatePower.delegatedAmount.toNumber()).to.equal(5 * FAM);
+      expect(delegatePower.delegatorCount).to.equal(1);
+
+      const stakeAccount = await program.account.stakeAccount.fetch(stakePda(delegator.publicKey));
+      expect(stakeAccount.delegate).to.be.null;
+
+      try {
+        await program.methods
+          .revokeDelegation()
+          .accounts(delegationAccounts(delegator))
+          .signers([delegator])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NoActiveDelegation");
+      }
+    });
+  });
+
+  describe("Team Vesting", () => {
+    let beneficiary: Keypair;
+    const vestingAuthorityPda = () =>
+      PublicKey.findProgramAddressSync([Buffer.from("vesting_authority")], program.programId)[0];
+
+    const vestingPdas = (index: number) => {
+      const indexBytes = new anchor.BN(index).toArrayLike(Buffer, "le", 8);
+      const [vesting] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vesting"), beneficiary.publicKey.toBuffer(), indexBytes],
+        program.programId
+      );
+      const [vestingVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vesting_vault"), beneficiary.publicKey.toBuffer(), indexBytes],
+        program.programId
+      );
+      return { vesting, vestingVault };
+    };
+
+    const createVesting = async (
+      signer: Keypair,
+      index: number,
+      amount: number,
+      startTime: number,
+      cliffDuration: number,
+      duration: number,
+      revocable: boolean
+    ) => {
+      const { vesting, vestingVault } = vestingPdas(index);
+      await program.methods
+        .createVesting(
+          new anchor.BN(index),
+          new anchor.BN(amount),
+          new anchor.BN(startTime),
+          new anchor.BN(cliffDuration),
+          new anchor.BN(duration),
+          revocable
+        )
+        .accounts({
+          config: configPda,
+          mintAuthority: mintAuthorityPda,
+          mint: mint.publicKey,
+          vesting,
+          vestingVault,
+          vestingAuthority: vestingAuthorityPda(),
+          beneficiary: beneficiary.publicKey,
+          authority: signer.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([signer])
+        .rpc();
+    };
+
+    const claimVested = (index: number) =>
+      program.methods
+        .claimVested()
+        .accounts({
+          ...vestingPdas(index),
+          vestingAuthority: vestingAuthorityPda(),
+          beneficiaryTokenAccount: getAssociatedTokenAddressSync(mint.publicKey, beneficiary.publicKey),
+          mint: mint.publicKey,
+          beneficiary: beneficiary.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([beneficiary])
+        .rpc();
+
+    const revokeVesting = (index: number) =>
+      program.methods
+        .revokeVesting()
+        .accounts({
+          config: configPda,
+          ...vestingPdas(index),
+          vestingAuthority: vestingAuthorityPda(),
+          mint: mint.publicKey,
+          authority: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([authority])
+        .rpc();
+
+    before(async () => {
+      beneficiary = await fundedKeypair();
+    });
+
+    it("Should not distribute team allocations outside a vesting schedule", async () => {
+      try {
+        await program.methods
+          .distributeTokens(new anchor.BN(FAM), { teamVesting: {} })
+          .accounts({
+            config: configPda,
+            mintAuthority: mintAuthorityPda,
+            mint: mint.publicKey,
+            recipientTokenAccount: getAssociatedTokenAddressSync(mint.publicKey, beneficiary.publicKey),
+            recipient: beneficiary.publicKey,
+            authority: authority.publicKey,
+            payer: authority.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([authority])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("VestingRequired");
+      }
+    });
+
+    it("Should only let the admin create a vesting", async () => {
+      try {
+        await createVesting(beneficiary, 0, 100 * FAM, await clusterTime(), 0, 1000, false);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+
+    it("Should release vested tokens linearly", async () => {
+      // Started half a schedule ago with no cliff
+      await createVesting(authority, 0, 100 * FAM, (await clusterTime()) - 1000, 0, 2000, false);
+      await claimVested(0);
+
+      const vesting = await program.account.vestingAccount.fetch(vestingPdas(0).vesting);
+      expect(vesting.claimedAmount.toNumber()).to.be.at.least(50 * FAM);
+      expect(vesting.claimedAmount.toNumber()).to.be.below(100 * FAM);
+
+      const tokenAccount = await getAccount(
+        provider.connection,
+        getAssociatedTokenAddressSync(mint.publicKey, beneficiary.publicKey)
+      );
+      expect(Number(tokenAccount.amount)).to.equal(vesting.claimedAmount.toNumber());
+    });
+
+    it("Should hold a second grant to the same beneficiary until its cliff", async () => {
+      await createVesting(authority, 1, 40 * FAM, await clusterTime(), 86400, 172800, true);
+
+      try {
+        await claimVested(1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NothingToClaim");
+      }
+    });
+
+    it("Should only revoke revocable vestings", async () => {
+      try {
+        await revokeVesting(0);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("VestingNotRevocable");
+      }
+
+      const before = await program.account.tokenConfig.fetch(configPda);
+      await revokeVesting(1);
+
+      // The unvested grant is burned and freed up in the team allocation
+      const vault = await getAccount(provider.connection, vestingPdas(1).vestingVault);
+      expect(Number(vault.amount)).to.equal(0);
+      const after = await program.account.tokenConfig.fetch(configPda);
+      expect(before.distributed[0].sub(after.distributed[0]).toNumber()).to.equal(40 * FAM);
+      expect(before.circulatingSupply.sub(after.circulatingSupply).toNumber()).to.equal(40 * FAM);
+
+      const vesting = await program.account.vestingAccount.fetch(vestingPdas(1).vesting);
+      expect(vesting.revoked).to.be.true;
+      expect(vesting.totalAmount.toNumber()).to.equal(0);
+    });
+  });
+
+  describe("Unbonding", () => {
+    let staker: Keypair;
+
+    const updateUnbondingPeriod = (signer: Keypair, period: number) =>
+      program.methods
+        .updateConfig(new anchor.BN(period), null, null, null)
+        .accounts({
+          config: configPda,
+          authority: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+
+    before(async () => {
+      staker = await fundedStaker(5 * FAM);
+    });
+
+    it("Should not start unbonding a locked stake", async () => {
+      try {
+        await program.methods
+          .requestUnstake(new anchor.BN(FAM))
+          .accounts({
+            config: configPda,
+            rewardVault: rewardVaultPda,
+            stakeAccount: stakePda(staker.publicKey),
+            user: staker.publicKey,
+          })
+          .signers([staker])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("StakeLocked");
+      }
+    });
+
+    it("Should not withdraw before any entry is released", async () => {
+      try {
+        await program.methods
+          .withdrawUnbonded()
+          .accounts({
+            stakeAccount: stakePda(staker.publicKey),
+            stakeTokenAccount: getAssociatedTokenAddressSync(mint.publicKey, stakeAuthorityPda, true),
+            stakeAuthority: stakeAuthorityPda,
+            userTokenAccount: getAssociatedTokenAddressSync(mint.publicKey, staker.publicKey),
+            mint: mint.publicKey,
+            user: staker.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([staker])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("UnbondingPeriodActive");
+      }
+
+      const stakeAccount = await program.account.stakeAccount.fetch(stakePda(staker.publicKey));
+      expect(stakeAccount.stakedAmount.toNumber()).to.equal(5 * FAM);
+      expect(stakeAccount.unbonding).to.be.empty;
+    });
+
+    it("Should let only the admin change the unbonding period", async () => {
+      try {
+        await updateUnbondingPeriod(staker, 259200);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+
+      try {
+        await updateUnbondingPeriod(authority, -1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidUnbondingPeriod");
+      }
+
+      await updateUnbondingPeriod(authority, 259200);
+      const config = await program.account.tokenConfig.fetch(configPda);
+      expect(config.unbondingPeriod.toNumber()).to.equal(259200);
+    });
+  });
+
+  describe("Proposal Lifecycle", () => {
+    const VOTING_PERIOD = 5;
+
+    let whale: Keypair;
+    let minnow: Keypair;
+
+    const updateGovernancePeriods = (votingPeriod: number, executionDelay: number) =>
+      program.methods
+        .updateConfig(null, null, new anchor.BN(votingPeriod), new anchor.BN(executionDelay))
+        .accounts({
+          config: configPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+    const waitForVotingEnd = async (proposal: PublicKey) => {
+      const { votingEndsAt } = await program.account.proposal.fetch(proposal);
+      while ((await clusterTime()) <= votingEndsAt.toNumber()) {
+        await sleep(1000);
+      }
+    };
+
+    const finalize = (proposal: PublicKey) =>
+      program.methods
+        .finalizeProposal()
+        .accounts({
+          config: configPda,
+          proposal,
+        })
+        .rpc();
+
+    const cancel = (proposal: PublicKey, signer: Keypair) =>
+      program.methods
+        .cancelProposal()
+        .accounts({
+          config: configPda,
+          proposal,
+          signer: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+
+    const markExecuted = (proposal: PublicKey, signer: Keypair) =>
+      program.methods
+        .markExecuted()
+        .accounts({
+          config: configPda,
+          proposal,
+          authority: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+
+    before(async () => {
+      // Enough stake on its own to meet the 10% quorum
+      const { circulatingSupply } = await program.account.tokenConfig.fetch(configPda);
+      whale = await fundedStaker(Math.floor(circulatingSupply.toNumber() / 4));
+      minnow = await fundedStaker(FAM);
+      await sleep(2000);
+    });
+
+    it("Should keep governance periods within range", async () => {
+      try {
+        await updateGovernancePeriods(0, 0);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidGovernancePeriod");
+      }
+
+      await updateGovernancePeriods(VOTING_PERIOD, 0);
+      const config = await program.account.tokenConfig.fetch(configPda);
+      expect(config.votingPeriod.toNumber()).to.equal(VOTING_PERIOD);
+      expect(config.executionDelay.toNumber()).to.equal(0);
+    });
+
+    it("Should only finalize once voting has closed", async () => {
+      const proposal = await createProposal(whale);
+
+      try {
+        await finalize(proposal);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("VotingPeriodActive");
+      }
+
+      await cancel(proposal, whale);
+    });
+
+    it("Should reject a proposal that misses quorum", async () => {
+      const proposal = await createProposal(whale);
+      await vote(proposal, minnow, stakePda(minnow.publicKey));
+      await waitForVotingEnd(proposal);
+
+      await finalize(proposal);
+      const finalized = await program.account.proposal.fetch(proposal);
+      expect(finalized.status).to.deep.equal({ rejected: {} });
+
+      try {
+        await cancel(proposal, whale);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("ProposalNotCancellable");
+      }
+
+      try {
+        await markExecuted(proposal, authority);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("ProposalNotPassed");
+      }
+    });
+
+    it("Should pass a proposal that meets quorum and let the admin mark it executed", async () => {
+      const proposal = await createProposal(whale);
+      await vote(proposal, whale, stakePda(whale.publicKey));
+      await waitForVotingEnd(proposal);
+      await finalize(proposal);
+      expect((await program.account.proposal.fetch(proposal)).status).to.deep.equal({ passed: {} });
+
+      try {
+        await finalize(proposal);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("ProposalNotActive");
+      }
+
+      try {
+        await markExecuted(proposal, whale);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+
+      await markExecuted(proposal, authority);
+      expect((await program.account.proposal.fetch(proposal)).status).to.deep.equal({ executed: {} });
+
+      try {
+        await cancel(proposal, authority);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("ProposalNotCancellable");
+      }
+    });
+
+    it("Should let only the proposer or admin cancel, and stop voting once cancelled", async () => {
+      const proposal = await createProposal(whale);
+
+      try {
+        await cancel(proposal, minnow);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+
+      await cancel(proposal, authority);
+      expect((await program.account.proposal.fetch(proposal)).status).to.deep.equal({ cancelled: {} });
+
+      try {
+        await vote(proposal, minnow, stakePda(minnow.publicKey));
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("ProposalNotActive");
+      }
+    });
+
+    it("Should only attach actions to economic parameter proposals", async () => {
+      try {
+        await createProposal(whale, { featureDevelopment: {} }, { marketplaceFeeRate: { 0: 250 } });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("ActionNotAllowed");
+      }
+    });
+
+    // game-treasury.ts runs next and executes this proposal once its
+    // treasury accepts the governance PDA
+    it("Should only execute a passed action against its target program", async () => {
+      const gameTreasuryId = anchor.workspace.GameTreasury.programId as PublicKey;
+      const [governance] = PublicKey.findProgramAddressSync([Buffer.from("governance")], program.programId);
+      const [treasury] = PublicKey.findProgramAddressSync([Buffer.from("treasury")], gameTreasuryId);
+
+      const execute = (proposal: PublicKey, targetProgram: PublicKey) =>
+        program.methods
+          .executeProposal()
+          .accounts({
+            config: configPda,
+            proposal,
+            governance,
+            targetConfig: treasury,
+            targetProgram,
+            executor: provider.wallet.publicKey,
+          })
+          .rpc();
+
+      const proposal = await createProposal(whale, { economicParameter: {} }, { marketplaceFeeRate: { 0: 250 } });
+
+      try {
+        await execute(proposal, gameTreasuryId);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("ProposalNotPassed");
+      }
+
+      await vote(proposal, whale, stakePda(whale.publicKey));
+      await waitForVotingEnd(proposal);
+      await finalize(proposal);
+
+      try {
+        await markExecuted(proposal, authority);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("ExecutableActionPending");
+      }
+
+      try {
+        await execute(proposal, anchor.workspace.MobToken.programId);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidTargetProgram");
+      }
+
+      expect((await program.account.proposal.fetch(proposal)).status).to.deep.equal({ passed: {} });
+    });
+  });
+
+  describe("Voting Power", () => {
+    let maxLocker: Keypair;
+    let proposer: Keypair;
+
+    // Mirrors calculate_voting_power: +3x at the maximum lock, decaying linearly to 1x at unlock
+    const expectedPower = (staked: number, lockEnd: number, at: number) => {
+      const remaining = Math.min(Math.max(lockEnd - at, 0), MAX_LOCK_DURATION);
+      const multiplierBps = 10000 + Math.floor((30000 * remaining) / MAX_LOCK_DURATION);
+      return Math.floor((staked * multiplierBps) / 10000);
+    };
+
+    const votedPower = async (proposal: PublicKey, voter: Keypair) => {
+      await vote(proposal, voter, stakePda(voter.publicKey));
+      const voteRecord = await program.account.voteRecord.fetch(votePda(proposal, voter.publicKey));
+      return voteRecord.votingPower.toNumber();
+    };
+
+    before(async () => {
+      maxLocker = await fundedKeypair();
+      await distribute(maxLocker.publicKey, 100 * FAM);
+      await stake(maxLocker, 100 * FAM, MAX_LOCK_DURATION);
+      proposer = await fundedStaker(10 * FAM);
+      await sleep(2000);
+    });
+
+    it("Should reject locks outside the allowed range", async () => {
+      await distribute(proposer.publicKey, FAM);
+
+      for (const lockDuration of [MIN_LOCK_DURATION - 1, MAX_LOCK_DURATION + 1]) {
+        try {
+          await stake(proposer, FAM, lockDuration);
+          expect.fail("Should have thrown an error");
+        } catch (error) {
+          expect(error.message).to.include("InvalidLockDuration");
+        }
+      }
+    });
+
+    it("Should give a maximum lock 4x voting power", async () => {
+      const stakeAccount = await program.account.stakeAccount.fetch(stakePda(maxLocker.publicKey));
+      expect(stakeAccount.votingPower.toNumber()).to.equal(4 * 100 * FAM);
+    });
+
+    it("Should decay the lock boost as the unlock approaches", async () => {
+      const maxLock = await program.account.stakeAccount.fetch(stakePda(maxLocker.publicKey));
+      const minLock = await program.account.stakeAccount.fetch(stakePda(proposer.publicKey));
+
+      const first = await createProposal(proposer);
+      const firstCreatedAt = (await program.account.proposal.fetch(first)).createdAt.toNumber();
+      const firstPower = await votedPower(first, maxLocker);
+      expect(firstPower).to.equal(expectedPower(100 * FAM, maxLock.lockEnd.toNumber(), firstCreatedAt));
+      expect(firstPower).to.be.below(4 * 100 * FAM);
+
+      // A one-week lock is barely boosted
+      const minLockPower = await votedPower(first, proposer);
+      expect(minLockPower).to.equal(expectedPower(10 * FAM, minLock.lockEnd.toNumber(), firstCreatedAt));
+      expect(minLockPower).to.be.within(10 * FAM, Math.floor(10.3 * FAM));
+
+      await sleep(2000);
+      const second = await createProposal(proposer);
+      const secondCreatedAt = (await program.account.proposal.fetch(second)).createdAt.toNumber();
+      const secondPower = await votedPower(second, maxLocker);
+      expect(secondCreatedAt).to.be.greaterThan(firstCreatedAt);
+      expect(secondPower).to.equal(expectedPower(100 * FAM, maxLock.lockEnd.toNumber(), secondCreatedAt));
+      expect(secondPower).to.be.below(firstPower);
+    });
+  });
+
+  describe("Distribution Allocations", () => {
+    // Percent of total supply, in DistributionType order
+    const CAP_PERCENTAGES = [20, 15, 10, 10, 30, 10, 5];
+    const MARKETING = 6;
+
+    const allocations = () =>
+      program.methods.getDistributionAllocations().accounts({ config: configPda }).view();
+
+    it("Should report the cap, distributed and remaining amount of every category", async () => {
+      const config = await program.account.tokenConfig.fetch(configPda);
+      const reported = await allocations();
+
+      expect(reported).to.have.lengthOf(CAP_PERCENTAGES.length);
+      reported.forEach((allocation, index) => {
+        const cap = config.totalSupply.muln(CAP_PERCENTAGES[index]).divn(100);
+        expect(allocation.cap.toString()).to.equal(cap.toString());
+        expect(allocation.distributed.toString()).to.equal(config.distributed[index].toString());
+        expect(allocation.remaining.toString()).to.equal(cap.sub(config.distributed[index]).toString());
+      });
+      expect(CAP_PERCENTAGES.reduce((sum, percentage) => sum + percentage)).to.equal(100);
+    });
+
+    it("Should stop a category at its cap", async () => {
+      const recipient = Keypair.generate().publicKey;
+      const remaining = (await allocations())[MARKETING].remaining.toNumber();
+
+      try {
+        await distribute(recipient, remaining + 1, { marketing: {} });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("ExceedsDistributionLimit");
+      }
+
+      await distribute(recipient, remaining, { marketing: {} });
+      const marketing = (await allocations())[MARKETING];
+      expect(marketing.remaining.toNumber()).to.equal(0);
+      expect(marketing.distributed.toString()).to.equal(marketing.cap.toString());
+
+      try {
+        await distribute(recipient, 1, { marketing: {} });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("ExceedsDistributionLimit");
+      }
+
+      // Other categories keep their own allowance
+      await distribute(recipient, FAM, { ecosystemIncentives: {} });
+    });
+  });
+
+  describe("Staking Rewards", () => {
+    const REWARD_RATE = FAM; // per second
+    let small: Keypair;
+    let large: Keypair;
+
+    const setRewardRate = (rate: number) =>
+      program.methods
+        .setRewardRate(new anchor.BN(rate))
+        .accounts({
+          config: configPda,
+          rewardVault: rewardVaultPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+    const claimRewards = (user: Keypair) =>
+      program.methods
+        .claimStakingRewards()
+        .accounts({
+          config: configPda,
+          stakeAccount: stakePda(user.publicKey),
+          rewardVault: rewardVaultPda,
+          stakeAuthority: stakeAuthorityPda,
+          userTokenAccount: getAssociatedTokenAddressSync(mint.publicKey, user.publicKey),
+          user: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user]);
+
+    const balance = async (user: Keypair) =>
+      Number(
+        (await getAccount(provider.connection, getAssociatedTokenAddressSync(mint.publicKey, user.publicKey)))
+          .amount
+      );
+
+    before(async () => {
+      const funder = await fundedKeypair();
+      await distribute(funder.publicKey, 1_000 * FAM, { stakingRewards: {} });
+      await transfer(
+        provider.connection,
+        funder,
+        getAssociatedTokenAddressSync(mint.publicKey, funder.publicKey),
+        rewardVaultPda,
+        funder,
+        1_000 * FAM
+      );
+
+      small = await fundedKeypair();
+      large = await fundedKeypair();
+      await distribute(small.publicKey, 1_000 * FAM);
+      await distribute(large.publicKey, 3_000 * FAM);
+    });
+
+    it("Should let only the admin set the reward rate", async () => {
+      try {
+        await program.methods
+          .setRewardRate(new anchor.BN(REWARD_RATE))
+          .accounts({
+            config: configPda,
+            rewardVault: rewardVaultPda,
+            authority: small.publicKey,
+          })
+          .signers([small])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+
+    it("Should split rewards between stakers by their share of the stake", async () => {
+      // Stake and claim both in one transaction so they share the accumulator
+      await provider.sendAndConfirm(
+        new Transaction().add(
+          await stakeTokens(small, 1_000 * FAM).instruction(),
+          await stakeTokens(large, 3_000 * FAM).instruction()
+        ),
+        [small, large]
+      );
+      await setRewardRate(REWARD_RATE);
+      await sleep(3000);
+
+      const smallBefore = await balance(small);
+      const largeBefore = await balance(large);
+      await provider.sendAndConfirm(
+        new Transaction().add(
+          await claimRewards(small).instruction(),
+          await claimRewards(large).instruction()
+        ),
+        [small, large]
+      );
+      const smallReward = (await balance(small)) - smallBefore;
+      const largeReward = (await balance(large)) - largeBefore;
+
+      expect(smallReward).to.be.greaterThan(0);
+      expect(Math.abs(largeReward - 3 * smallReward)).to.be.at.most(3);
+
+      // Each claim is exactly what accrued on the stake since it was last settled
+      const config = await program.account.tokenConfig.fetch(configPda);
+      const accrued = new anchor.BN(1_000 * FAM)
+        .mul(config.accRewardPerShare)
+        .div(new anchor.BN(10).pow(new anchor.BN(12)));
+      const stakeAccount = await program.account.stakeAccount.fetch(stakePda(small.publicKey));
+      expect(stakeAccount.rewardDebt.toString()).to.equal(accrued.toString());
+      expect(stakeAccount.pendingRewards.toNumber()).to.equal(0);
+
+      const vault = await getAccount(provider.connection, rewardVaultPda);
+      expect(config.unclaimedRewards.toNumber()).to.be.at.most(Number(vault.amount));
+    });
+
+    it("Should not accrue for stake added after the rate is cleared", async () => {
+      await setRewardRate(0);
+      const lateStaker = await fundedStaker(1_000 * FAM);
+      await sleep(2000);
+
+      try {
+        await claimRewards(lateStaker).rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NothingToClaim");
+      }
+    });
+  });
+});
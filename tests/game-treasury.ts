@@ -0,0 +1,592 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { GameTreasury } from "../target/types/game_treasury";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import { createMint, getOrCreateAssociatedTokenAccount, mintTo } from "@solana/spl-token";
+import { expect } from "chai";
+
+describe("Game Treasury Program", () => {
+  // Configure the client to use the local cluster
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.GameTreasury as Program<GameTreasury>;
+
+  // Test accounts
+  let authority: Keypair;
+  let signerA: Keypair;
+  let signerB: Keypair;
+  let guardian: Keypair;
+  let mobMint: PublicKey;
+  let famMint: PublicKey;
+  let treasuryPda: PublicKey;
+  let treasuryAuthorityPda: PublicKey;
+
+  const WITHDRAWAL_DELAY = 86400; // The minimum the program allows
+
+  const withdrawalPda = (id: number) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("withdrawal"), new anchor.BN(id).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+  before(async () => {
+    authority = Keypair.generate();
+    signerA = Keypair.generate();
+    signerB = Keypair.generate();
+    guardian = Keypair.generate();
+
+    for (const account of [authority, signerA, signerB, guardian]) {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(account.publicKey, 2 * LAMPORTS_PER_SOL)
+      );
+    }
+
+    mobMint = await createMint(provider.connection, authority, authority.publicKey, null, 9);
+    famMint = await createMint(provider.connection, authority, authority.publicKey, null, 6);
+
+    [treasuryPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury")],
+      program.programId
+    );
+    [treasuryAuthorityPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury_authority")],
+      program.programId
+    );
+  });
+
+  describe("Initialization", () => {
+    it("Should initialize the treasury", async () => {
+      await program.methods
+        .initialize()
+        .accounts({
+          treasury: treasuryPda,
+          treasuryAuthority: treasuryAuthorityPda,
+          mobMint,
+          famMint,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+      const treasury = await program.account.treasury.fetch(treasuryPda);
+      expect(treasury.authority.toString()).to.equal(authority.publicKey.toString());
+      expect(treasury.withdrawalSigners).to.be.empty;
+    });
+  });
+
+  describe("Emergency Withdrawal Council", () => {
+    const executeAccounts = async (id: number) => {
+      const treasuryTokenAccount = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        authority,
+        mobMint,
+        treasuryAuthorityPda,
+        true
+      );
+      const recipientTokenAccount = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        authority,
+        mobMint,
+        signerA.publicKey
+      );
+      return {
+        treasury: treasuryPda,
+        withdrawal: withdrawalPda(id),
+        treasuryTokenAccount: treasuryTokenAccount.address,
+        treasuryAuthority: treasuryAuthorityPda,
+        recipientTokenAccount: recipientTokenAccount.address,
+        mint: mobMint,
+        executor: authority.publicKey,
+      };
+    };
+
+    const setCouncil = (threshold: number, councilGuardian: PublicKey) =>
+      program.methods
+        .setWithdrawalCouncil(
+          [signerA.publicKey, signerB.publicKey],
+          threshold,
+          councilGuardian,
+          new anchor.BN(WITHDRAWAL_DELAY)
+        )
+        .accounts({
+          treasury: treasuryPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+    it("Should require a guardian and more than one approver at bootstrap", async () => {
+      try {
+        await setCouncil(1, guardian.publicKey);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidThreshold");
+      }
+
+      try {
+        await setCouncil(2, PublicKey.default);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidGuardian");
+      }
+    });
+
+    it("Should let the admin bootstrap a 2-of-2 council", async () => {
+      await setCouncil(2, guardian.publicKey);
+
+      const treasury = await program.account.treasury.fetch(treasuryPda);
+      expect(treasury.withdrawalSigners.map((key) => key.toString())).to.deep.equal([
+        signerA.publicKey.toString(),
+        signerB.publicKey.toString(),
+      ]);
+      expect(treasury.withdrawalThreshold).to.equal(2);
+      expect(treasury.guardian.toString()).to.equal(guardian.publicKey.toString());
+    });
+
+    it("Should not let the council be replaced outside the proposal flow", async () => {
+      try {
+        await setCouncil(2, authority.publicKey);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("CouncilAlreadySet");
+      }
+    });
+
+    it("Should reject withdrawal proposals from outside the council", async () => {
+      try {
+        await program.methods
+          .proposeWithdrawal(new anchor.BN(1_000), { mob: {} }, authority.publicKey)
+          .accounts({
+            treasury: treasuryPda,
+            withdrawal: withdrawalPda(0),
+            proposer: authority.publicKey,
+          })
+          .signers([authority])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NotWithdrawalSigner");
+      }
+    });
+
+    it("Should start the timelock only once the threshold is reached", async () => {
+      await program.methods
+        .proposeWithdrawal(new anchor.BN(1_000), { mob: {} }, signerA.publicKey)
+        .accounts({
+          treasury: treasuryPda,
+          withdrawal: withdrawalPda(0),
+          proposer: signerA.publicKey,
+        })
+        .signers([signerA])
+        .rpc();
+
+      let withdrawal = await program.account.withdrawalProposal.fetch(withdrawalPda(0));
+      expect(withdrawal.executableAt.toNumber()).to.equal(0);
+      expect(withdrawal.status).to.deep.equal({ pending: {} });
+
+      try {
+        await program.methods
+          .executeWithdrawal()
+          .accounts(await executeAccounts(0))
+          .signers([authority])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("InsufficientApprovals");
+      }
+
+      await program.methods
+        .approveWithdrawal()
+        .accounts({
+          treasury: treasuryPda,
+          withdrawal: withdrawalPda(0),
+          signer: signerB.publicKey,
+        })
+        .signers([signerB])
+        .rpc();
+
+      withdrawal = await program.account.withdrawalProposal.fetch(withdrawalPda(0));
+      expect(withdrawal.approvals).to.have.lengthOf(2);
+      expect(withdrawal.executableAt.toNumber()).to.be.at.least(
+        withdrawal.createdAt.toNumber() + WITHDRAWAL_DELAY
+      );
+
+      try {
+        await program.methods
+          .executeWithdrawal()
+          .accounts(await executeAccounts(0))
+          .signers([authority])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("WithdrawalDelayActive");
+      }
+    });
+
+    it("Should reject vetoes from anyone but the guardian", async () => {
+      try {
+        await program.methods
+          .vetoWithdrawal()
+          .accounts({
+            treasury: treasuryPda,
+            withdrawal: withdrawalPda(0),
+            guardian: signerB.publicKey,
+          })
+          .signers([signerB])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+
+    it("Should let the guardian veto an approved withdrawal during the timelock", async () => {
+      await program.methods
+        .vetoWithdrawal()
+        .accounts({
+          treasury: treasuryPda,
+          withdrawal: withdrawalPda(0),
+          guardian: guardian.publicKey,
+        })
+        .signers([guardian])
+        .rpc();
+
+      const withdrawal = await program.account.withdrawalProposal.fetch(withdrawalPda(0));
+      expect(withdrawal.status).to.deep.equal({ vetoed: {} });
+
+      try {
+        await program.methods
+          .executeWithdrawal()
+          .accounts(await executeAccounts(0))
+          .signers([authority])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("WithdrawalNotPending");
+      }
+    });
+
+    it("Should timelock council updates and let the guardian veto them", async () => {
+      const executeCouncilUpdate = () =>
+        program.methods
+          .executeCouncilUpdate()
+          .accounts({
+            treasury: treasuryPda,
+            withdrawal: withdrawalPda(1),
+            executor: authority.publicKey,
+          })
+          .signers([authority])
+          .rpc();
+
+      await program.methods
+        .proposeCouncilUpdate(
+          [signerA.publicKey, signerB.publicKey],
+          2,
+          signerA.publicKey,
+          new anchor.BN(WITHDRAWAL_DELAY)
+        )
+        .accounts({
+          treasury: treasuryPda,
+          withdrawal: withdrawalPda(1),
+          proposer: signerA.publicKey,
+        })
+        .signers([signerA])
+        .rpc();
+
+      await program.methods
+        .approveWithdrawal()
+        .accounts({
+          treasury: treasuryPda,
+          withdrawal: withdrawalPda(1),
+          signer: signerB.publicKey,
+        })
+        .signers([signerB])
+        .rpc();
+
+      try {
+        await executeCouncilUpdate();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("WithdrawalDelayActive");
+      }
+
+      await program.methods
+        .vetoWithdrawal()
+        .accounts({
+          treasury: treasuryPda,
+          withdrawal: withdrawalPda(1),
+          guardian: guardian.publicKey,
+        })
+        .signers([guardian])
+        .rpc();
+
+      try {
+        await executeCouncilUpdate();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("WithdrawalNotPending");
+      }
+
+      const treasury = await program.account.treasury.fetch(treasuryPda);
+      expect(treasury.guardian.toString()).to.equal(guardian.publicKey.toString());
+    });
+  });
+
+  describe("Reward Budgets", () => {
+    let distributor: Keypair;
+    let treasuryTokenAccount: PublicKey;
+
+    const MOB = 1_000_000_000;
+
+    const distributeAccounts = async (recipient: PublicKey, signer: PublicKey) => {
+      const recipientTokenAccount = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        authority,
+        mobMint,
+        recipient
+      );
+      const [recipientRewards] = PublicKey.findProgramAddressSync(
+        [Buffer.from("recipient_rewards"), recipient.toBuffer()],
+        program.programId
+      );
+      return {
+        treasury: treasuryPda,
+        treasuryTokenAccount,
+        treasuryAuthority: treasuryAuthorityPda,
+        recipientTokenAccount: recipientTokenAccount.address,
+        recipient,
+        recipientRewards,
+        mobMint,
+        distributor: signer,
+      };
+    };
+
+    const budgets = (mission: number) =>
+      [mission, 5_000, 2_500, 5_000, 10_000, 5_000].map((amount) =>
+        new anchor.BN(amount).mul(new anchor.BN(MOB))
+      );
+
+    before(async () => {
+      distributor = Keypair.generate();
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(distributor.publicKey, LAMPORTS_PER_SOL)
+      );
+
+      const account = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        authority,
+        mobMint,
+        treasuryAuthorityPda,
+        true
+      );
+      treasuryTokenAccount = account.address;
+      await mintTo(provider.connection, authority, mobMint, treasuryTokenAccount, authority, 100 * MOB);
+    });
+
+    it("Should not treat the admin as a distributor", async () => {
+      try {
+        await program.methods
+          .distributeRewards(new anchor.BN(MOB), { mission: {} })
+          .accounts(await distributeAccounts(signerA.publicKey, authority.publicKey))
+          .signers([authority])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("UnauthorizedDistributor");
+      }
+    });
+
+    it("Should reject budgets and caps above their ceilings", async () => {
+      try {
+        await program.methods
+          .updateRewardBudgets(null, budgets(60_000), null)
+          .accounts({ treasury: treasuryPda, authority: authority.publicKey })
+          .signers([authority])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("ExcessiveRewardBudget");
+      }
+
+      try {
+        await program.methods
+          .updateRewardBudgets(null, null, new anchor.BN(2_000).mul(new anchor.BN(MOB)))
+          .accounts({ treasury: treasuryPda, authority: authority.publicKey })
+          .signers([authority])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("ExcessiveRecipientCap");
+      }
+    });
+
+    it("Should cap what one recipient receives per day", async () => {
+      await program.methods
+        .addDistributor(distributor.publicKey)
+        .accounts({ treasury: treasuryPda, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+      await program.methods
+        .updateRewardBudgets(null, null, new anchor.BN(3 * MOB))
+        .accounts({ treasury: treasuryPda, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+
+      await program.methods
+        .distributeRewards(new anchor.BN(2.5 * MOB), { mission: {} })
+        .accounts(await distributeAccounts(signerA.publicKey, distributor.publicKey))
+        .signers([distributor])
+        .rpc();
+
+      try {
+        await program.methods
+          .distributeRewards(new anchor.BN(MOB), { mission: {} })
+          .accounts(await distributeAccounts(signerA.publicKey, distributor.publicKey))
+          .signers([distributor])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("RecipientDailyCapExceeded");
+      }
+    });
+
+    it("Should stop a reward type once its epoch budget is spent", async () => {
+      // 2.5 MOB of Mission rewards went out above; allow 3 MOB for the epoch
+      await program.methods
+        .updateRewardBudgets(null, [new anchor.BN(3 * MOB), ...budgets(0).slice(1)], null)
+        .accounts({ treasury: treasuryPda, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+
+      try {
+        await program.methods
+          .distributeRewards(new anchor.BN(MOB), { mission: {} })
+          .accounts(await distributeAccounts(signerB.publicKey, distributor.publicKey))
+          .signers([distributor])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("EpochBudgetExceeded");
+      }
+
+      const treasury = await program.account.treasury.fetch(treasuryPda);
+      expect(treasury.rewardTypeDistributed[0].toNumber()).to.equal(2.5 * MOB);
+    });
+  });
+
+  describe("Governance", () => {
+    const famToken = anchor.workspace.FamToken;
+    let stranger: Keypair;
+    let governancePda: PublicKey;
+
+    before(async () => {
+      stranger = Keypair.generate();
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(stranger.publicKey, LAMPORTS_PER_SOL)
+      );
+      [governancePda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("governance")],
+        famToken.programId
+      );
+    });
+
+    it("Should reject parameter updates from anyone but the admin or governance", async () => {
+      try {
+        await program.methods
+          .updateConfig(100, null, null, null, null)
+          .accounts({
+            treasury: treasuryPda,
+            authority: stranger.publicKey,
+          })
+          .signers([stranger])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+
+    it("Should apply a passed FAM proposal through the governance PDA", async () => {
+      await program.methods
+        .updateConfig(null, null, null, governancePda, null)
+        .accounts({
+          treasury: treasuryPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+      // Passed and finalized by the fam-token tests
+      const [passed] = (await famToken.account.proposal.all()).filter(
+        ({ account }) => account.status.passed && account.action?.marketplaceFeeRate
+      );
+      expect(passed).to.not.be.undefined;
+
+      await famToken.methods
+        .executeProposal()
+        .accounts({
+          config: PublicKey.findProgramAddressSync([Buffer.from("config")], famToken.programId)[0],
+          proposal: passed.publicKey,
+          governance: governancePda,
+          targetConfig: treasuryPda,
+          targetProgram: program.programId,
+          executor: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      const treasury = await program.account.treasury.fetch(treasuryPda);
+      expect(treasury.marketplaceFeeRate).to.equal(250);
+      expect(treasury.governance.toString()).to.equal(governancePda.toString());
+      const proposal = await famToken.account.proposal.fetch(passed.publicKey);
+      expect(proposal.status).to.deep.equal({ executed: {} });
+    });
+
+    it("Should let the admin set the first staking reward vault but not re-point it", async () => {
+      const setVault = (vault: PublicKey, signer: Keypair) =>
+        program.methods
+          .updateConfig(null, null, null, null, vault)
+          .accounts({
+            treasury: treasuryPda,
+            authority: signer.publicKey,
+          })
+          .signers([signer])
+          .rpc();
+
+      const [rewardVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from("reward_vault")],
+        famToken.programId
+      );
+
+      try {
+        await setVault(rewardVault, stranger);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+
+      await setVault(rewardVault, authority);
+      const treasury = await program.account.treasury.fetch(treasuryPda);
+      expect(treasury.stakingRewardVault.toString()).to.equal(rewardVault.toString());
+
+      // Re-pointing the vault needs a governance proposal
+      try {
+        await setVault(Keypair.generate().publicKey, authority);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+  });
+});
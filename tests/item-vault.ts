@@ -0,0 +1,483 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { ItemVault } from "../target/types/item_vault";
+import { CharacterNft } from "../target/types/character_nft";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SYSVAR_RENT_PUBKEY,
+  SYSVAR_SLOT_HASHES_PUBKEY,
+} from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { createHash, randomBytes } from "crypto";
+
+describe("Item Vault Program", () => {
+  // Configure the client to use the local cluster
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.ItemVault as Program<ItemVault>;
+
+  // Test accounts
+  let authority: Keypair;
+  let gameServer: Keypair;
+  let player: Keypair;
+  let configPda: PublicKey;
+  let mintAuthorityPda: PublicKey;
+
+  const weaponPda = (mint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("weapon"), mint.toBuffer()],
+      program.programId
+    )[0];
+
+  const mintWeapon = async (minter: Keypair) => {
+    const mint = Keypair.generate();
+    await program.methods
+      .mintWeapon("Tommy Gun", { pistol: {} }, { common: {} }, 20)
+      .accounts({
+        config: configPda,
+        mint: mint.publicKey,
+        mintAuthority: mintAuthorityPda,
+        tokenAccount: getAssociatedTokenAddressSync(mint.publicKey, player.publicKey),
+        weapon: weaponPda(mint.publicKey),
+        payer: authority.publicKey,
+        minter: minter.publicKey,
+        owner: player.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([mint, authority, minter])
+      .rpc();
+    return mint.publicKey;
+  };
+
+  const manageMinter = (method: "addMinter" | "removeMinter", minter: PublicKey, signer: Keypair) =>
+    program.methods[method](minter)
+      .accounts({
+        config: configPda,
+        authority: signer.publicKey,
+      })
+      .signers([signer])
+      .rpc();
+
+  before(async () => {
+    authority = Keypair.generate();
+    gameServer = Keypair.generate();
+    player = Keypair.generate();
+
+    for (const account of [authority, gameServer, player]) {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(account.publicKey, 2 * LAMPORTS_PER_SOL)
+      );
+    }
+
+    [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      program.programId
+    );
+    [mintAuthorityPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_authority")],
+      program.programId
+    );
+  });
+
+  describe("Initialization", () => {
+    it("Should initialize with the admin as the only minter", async () => {
+      const weaponCollection = await createMint(provider.connection, authority, authority.publicKey, null, 0);
+      const vehicleCollection = await createMint(provider.connection, authority, authority.publicKey, null, 0);
+
+      await program.methods
+        .initialize()
+        .accounts({
+          config: configPda,
+          mintAuthority: mintAuthorityPda,
+          weaponCollection,
+          vehicleCollection,
+          authority: authority.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+      const config = await program.account.itemConfig.fetch(configPda);
+      expect(config.minters.map((key) => key.toString())).to.deep.equal([authority.publicKey.toString()]);
+    });
+  });
+
+  describe("Minter Roles", () => {
+    it("Should not mint a weapon for a key that isn't a minter", async () => {
+      try {
+        await mintWeapon(gameServer);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("UnauthorizedMinter");
+      }
+    });
+
+    it("Should not let a non-admin add a minter", async () => {
+      try {
+        await manageMinter("addMinter", gameServer.publicKey, gameServer);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+
+    it("Should let an added minter mint weapons", async () => {
+      await manageMinter("addMinter", gameServer.publicKey, authority);
+      const mint = await mintWeapon(gameServer);
+
+      const tokenAccount = await getAccount(
+        provider.connection,
+        getAssociatedTokenAddressSync(mint, player.publicKey)
+      );
+      expect(Number(tokenAccount.amount)).to.equal(1);
+
+      const weapon = await program.account.weapon.fetch(weaponPda(mint));
+      expect(weapon.owner.toString()).to.equal(player.publicKey.toString());
+
+      try {
+        await manageMinter("addMinter", gameServer.publicKey, authority);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("MinterAlreadyExists");
+      }
+    });
+
+    it("Should stop a removed minter from minting", async () => {
+      await manageMinter("removeMinter", gameServer.publicKey, authority);
+
+      try {
+        await mintWeapon(gameServer);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("UnauthorizedMinter");
+      }
+
+      try {
+        await manageMinter("removeMinter", gameServer.publicKey, authority);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("MinterNotFound");
+      }
+    });
+  });
+
+  describe("Weapon Drops", () => {
+    const commitment = (secret: Buffer) => Array.from(createHash("sha256").update(secret).digest());
+
+    let dropPda: PublicKey;
+    let playerSecret: Buffer;
+    let serverSecret: Buffer;
+
+    const requestDrop = async (minter: Keypair) => {
+      playerSecret = randomBytes(32);
+      serverSecret = randomBytes(32);
+      await program.methods
+        .requestWeaponDrop({
+          commitReveal: {
+            playerCommitment: commitment(playerSecret),
+            serverCommitment: commitment(serverSecret),
+          },
+        })
+        .accounts({
+          config: configPda,
+          pendingDrop: dropPda,
+          owner: player.publicKey,
+          minter: minter.publicKey,
+          vrfResult: null,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([player, minter])
+        .rpc();
+    };
+
+    const claimDrop = async (secrets: { playerSecret: number[]; serverSecret: number[] }) => {
+      const mint = Keypair.generate();
+      await program.methods
+        .claimWeaponDrop(secrets)
+        .accounts({
+          config: configPda,
+          pendingDrop: dropPda,
+          mint: mint.publicKey,
+          mintAuthority: mintAuthorityPda,
+          tokenAccount: getAssociatedTokenAddressSync(mint.publicKey, player.publicKey),
+          weapon: weaponPda(mint.publicKey),
+          owner: player.publicKey,
+          vrfResult: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([mint, player])
+        .rpc();
+      return mint.publicKey;
+    };
+
+    before(async () => {
+      [dropPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("weapon_drop"), player.publicKey.toBuffer()],
+        program.programId
+      );
+    });
+
+    it("Should only let a minter grant a drop", async () => {
+      try {
+        await requestDrop(gameServer);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("UnauthorizedMinter");
+      }
+    });
+
+    it("Should reject secrets that don't match the commitments", async () => {
+      await requestDrop(authority);
+
+      try {
+        await claimDrop({
+          playerSecret: Array.from(playerSecret),
+          serverSecret: Array.from(randomBytes(32)),
+        });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidReveal");
+      }
+    });
+
+    it("Should mint the rolled weapon once both secrets are revealed", async () => {
+      const mint = await claimDrop({
+        playerSecret: Array.from(playerSecret),
+        serverSecret: Array.from(serverSecret),
+      });
+
+      const tokenAccount = await getAccount(
+        provider.connection,
+        getAssociatedTokenAddressSync(mint, player.publicKey)
+      );
+      expect(Number(tokenAccount.amount)).to.equal(1);
+
+      const weapon = await program.account.weapon.fetch(weaponPda(mint));
+      expect(weapon.owner.toString()).to.equal(player.publicKey.toString());
+      expect(weapon.baseDamage).to.be.within(10, 100);
+      expect(await provider.connection.getAccountInfo(dropPda)).to.be.null;
+    });
+
+    it("Should let only a minter cancel an unrevealed drop", async () => {
+      await requestDrop(authority);
+
+      const cancel = (minter: Keypair) =>
+        program.methods
+          .cancelWeaponDrop()
+          .accounts({
+            config: configPda,
+            pendingDrop: dropPda,
+            owner: player.publicKey,
+            minter: minter.publicKey,
+          })
+          .signers([minter])
+          .rpc();
+
+      try {
+        await cancel(gameServer);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("UnauthorizedMinter");
+      }
+
+      await cancel(authority);
+      expect(await provider.connection.getAccountInfo(dropPda)).to.be.null;
+    });
+  });
+
+  describe("Character Equips", () => {
+    const characterProgram = anchor.workspace.CharacterNft as Program<CharacterNft>;
+    const TOKEN_METADATA_PROGRAM_ID = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+    let playerCharacter: { mint: PublicKey; character: PublicKey };
+    let rivalCharacter: { mint: PublicKey; character: PublicKey };
+    let weaponMint: PublicKey;
+
+    const characterPda = (seed: string, mint: PublicKey) =>
+      PublicKey.findProgramAddressSync([Buffer.from(seed), mint.toBuffer()], characterProgram.programId)[0];
+
+    const metadataPdas = (mint: PublicKey) => {
+      const [metadata] = PublicKey.findProgramAddressSync(
+        [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+        TOKEN_METADATA_PROGRAM_ID
+      );
+      const [masterEdition] = PublicKey.findProgramAddressSync(
+        [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer(), Buffer.from("edition")],
+        TOKEN_METADATA_PROGRAM_ID
+      );
+      return { metadata, masterEdition };
+    };
+
+    // Players mint characters through the commit-reveal flow
+    const mintCharacter = async (owner: Keypair) => {
+      const [characterConfig] = PublicKey.findProgramAddressSync(
+        [Buffer.from("config")],
+        characterProgram.programId
+      );
+      const [mintCommitment] = PublicKey.findProgramAddressSync(
+        [Buffer.from("commitment"), owner.publicKey.toBuffer()],
+        characterProgram.programId
+      );
+      const [proceedsVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from("proceeds")],
+        characterProgram.programId
+      );
+      const [characterMintAuthority] = PublicKey.findProgramAddressSync(
+        [Buffer.from("mint_authority")],
+        characterProgram.programId
+      );
+      const { collectionMint } = await characterProgram.account.config.fetch(characterConfig);
+      const collection = metadataPdas(collectionMint);
+
+      const secret = randomBytes(32);
+      await characterProgram.methods
+        .commitCharacter(Array.from(createHash("sha256").update(secret).digest()))
+        .accounts({
+          config: characterConfig,
+          mintCommitment,
+          proceedsVault,
+          payer: owner.publicKey,
+          owner: owner.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+
+      const { commitSlot } = await characterProgram.account.mintCommitment.fetch(mintCommitment);
+      while ((await provider.connection.getSlot()) <= commitSlot.toNumber() + 1) {
+        await new Promise((resolve) => setTimeout(resolve, 400));
+      }
+
+      const mint = Keypair.generate();
+      const character = characterPda("character", mint.publicKey);
+      await characterProgram.methods
+        .revealCharacter("Made Man", "MOB", "https://metadata.mafianft.com/characters/rolled.json", Array.from(secret))
+        .accounts({
+          config: characterConfig,
+          mintCommitment,
+          mint: mint.publicKey,
+          mintAuthority: characterMintAuthority,
+          tokenAccount: getAssociatedTokenAddressSync(mint.publicKey, owner.publicKey),
+          character,
+          ...metadataPdas(mint.publicKey),
+          collectionMint,
+          collectionMetadata: collection.metadata,
+          collectionMasterEdition: collection.masterEdition,
+          payer: owner.publicKey,
+          owner: owner.publicKey,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        })
+        .signers([owner, mint])
+        .rpc();
+
+      return { mint: mint.publicKey, character };
+    };
+
+    const equip = (character: PublicKey) =>
+      program.methods
+        .equipWeapon()
+        .accounts({
+          weapon: weaponPda(weaponMint),
+          character,
+          owner: player.publicKey,
+        })
+        .signers([player])
+        .rpc();
+
+    const unequip = (character: PublicKey) =>
+      program.methods
+        .unequipWeapon()
+        .accounts({
+          weapon: weaponPda(weaponMint),
+          character,
+          owner: player.publicKey,
+        })
+        .signers([player])
+        .rpc();
+
+    before(async () => {
+      playerCharacter = await mintCharacter(player);
+      rivalCharacter = await mintCharacter(gameServer);
+      weaponMint = await mintWeapon(authority);
+    });
+
+    it("Should not equip a weapon to another player's character", async () => {
+      try {
+        await equip(rivalCharacter.character);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NotOwner");
+      }
+    });
+
+    it("Should record the character a weapon is equipped to", async () => {
+      await equip(playerCharacter.character);
+
+      const weapon = await program.account.weapon.fetch(weaponPda(weaponMint));
+      expect(weapon.isEquipped).to.be.true;
+      expect(weapon.equippedTo.toString()).to.equal(playerCharacter.character.toString());
+    });
+
+    it("Should only unequip a weapon from the character it is equipped to", async () => {
+      try {
+        await unequip(rivalCharacter.character);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NotEquippedToCharacter");
+      }
+
+      await unequip(playerCharacter.character);
+
+      const weapon = await program.account.weapon.fetch(weaponPda(weaponMint));
+      expect(weapon.isEquipped).to.be.false;
+      expect(weapon.equippedTo.toString()).to.equal(PublicKey.default.toString());
+    });
+
+    it("Should not equip a weapon to a staked character", async () => {
+      await characterProgram.methods
+        .stakeCharacter()
+        .accounts({
+          character: playerCharacter.character,
+          mint: playerCharacter.mint,
+          ownerTokenAccount: getAssociatedTokenAddressSync(playerCharacter.mint, player.publicKey),
+          vaultTokenAccount: characterPda("character_vault", playerCharacter.mint),
+          stakingAuthority: PublicKey.findProgramAddressSync(
+            [Buffer.from("staking_authority")],
+            characterProgram.programId
+          )[0],
+          owner: player.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        })
+        .signers([player])
+        .rpc();
+
+      try {
+        await equip(playerCharacter.character);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("CharacterStaked");
+      }
+    });
+  });
+});
@@ -0,0 +1,232 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { MobToken } from "../target/types/mob_token";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  getAccount,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+import { expect } from "chai";
+
+describe("MOB Token Program", () => {
+  // Configure the client to use the local cluster
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MobToken as Program<MobToken>;
+
+  // Test accounts
+  let authority: Keypair;
+  let backend: Keypair;
+  let stranger: Keypair;
+  let player: Keypair;
+  let mint: Keypair;
+  let configPda: PublicKey;
+  let mintAuthorityPda: PublicKey;
+
+  const MOB = 1_000_000_000;
+  const DAILY_EMISSION = 5 * MOB;
+
+  const initialize = async (issuers: PublicKey[]) => {
+    await program.methods
+      .initialize(new anchor.BN(DAILY_EMISSION), issuers)
+      .accounts({
+        config: configPda,
+        mintAuthority: mintAuthorityPda,
+        mint: mint.publicKey,
+        authority: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([mint, authority])
+      .rpc();
+  };
+
+  const mintReward = async (issuer: Keypair, amount: number) => {
+    const [playerDataPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("player"), player.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .mintReward(new anchor.BN(amount), { mission: {} })
+      .accounts({
+        config: configPda,
+        mintAuthority: mintAuthorityPda,
+        mint: mint.publicKey,
+        playerData: playerDataPda,
+        recipientTokenAccount: getAssociatedTokenAddressSync(mint.publicKey, player.publicKey),
+        recipient: player.publicKey,
+        issuer: issuer.publicKey,
+        payer: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([issuer, authority])
+      .rpc();
+  };
+
+  const manageIssuer = (method: "addIssuer" | "removeIssuer", issuer: PublicKey, signer: Keypair) =>
+    program.methods[method](issuer)
+      .accounts({
+        config: configPda,
+        authority: signer.publicKey,
+      })
+      .signers([signer])
+      .rpc();
+
+  before(async () => {
+    authority = Keypair.generate();
+    backend = Keypair.generate();
+    stranger = Keypair.generate();
+    player = Keypair.generate();
+    mint = Keypair.generate();
+
+    for (const account of [authority, stranger]) {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(account.publicKey, 2 * LAMPORTS_PER_SOL)
+      );
+    }
+
+    [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      program.programId
+    );
+    [mintAuthorityPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_authority")],
+      program.programId
+    );
+  });
+
+  describe("Initialization", () => {
+    it("Should reject duplicate issuers at initialize", async () => {
+      try {
+        await initialize([backend.publicKey, backend.publicKey]);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("IssuerAlreadyExists");
+      }
+    });
+
+    it("Should initialize with the starting issuers", async () => {
+      await initialize([backend.publicKey]);
+
+      const config = await program.account.tokenConfig.fetch(configPda);
+      expect(config.authority.toString()).to.equal(authority.publicKey.toString());
+      expect(config.issuers.map((key) => key.toString())).to.deep.equal([backend.publicKey.toString()]);
+    });
+  });
+
+  describe("Reward Issuers", () => {
+    it("Should mint a reward signed by an authorized issuer", async () => {
+      await mintReward(backend, MOB);
+
+      const tokenAccount = await getAccount(
+        provider.connection,
+        getAssociatedTokenAddressSync(mint.publicKey, player.publicKey)
+      );
+      expect(Number(tokenAccount.amount)).to.equal(MOB);
+    });
+
+    it("Should not mint a reward signed by an unknown key", async () => {
+      try {
+        await mintReward(stranger, MOB);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("UnauthorizedIssuer");
+      }
+    });
+
+    it("Should not let a non-admin add an issuer", async () => {
+      try {
+        await manageIssuer("addIssuer", stranger.publicKey, stranger);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+
+    it("Should let the admin add an issuer once", async () => {
+      await manageIssuer("addIssuer", stranger.publicKey, authority);
+      await mintReward(stranger, MOB);
+
+      try {
+        await manageIssuer("addIssuer", stranger.publicKey, authority);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("IssuerAlreadyExists");
+      }
+    });
+
+    it("Should stop a removed issuer from minting", async () => {
+      await manageIssuer("removeIssuer", stranger.publicKey, authority);
+
+      try {
+        await mintReward(stranger, MOB);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("UnauthorizedIssuer");
+      }
+
+      try {
+        await manageIssuer("removeIssuer", stranger.publicKey, authority);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("IssuerNotFound");
+      }
+    });
+  });
+
+  describe("Emission Budgets", () => {
+    const updateBudgets = (budgets: number[]) =>
+      program.methods
+        .updateConfig(null, null, null, budgets.map((budget) => new anchor.BN(budget)), null)
+        .accounts({
+          config: configPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+    it("Should reject a reward type budget above the daily emission", async () => {
+      try {
+        await updateBudgets([DAILY_EMISSION + 1, 0, 0, 0, 0, 0, 0, 0]);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidRewardBudget");
+      }
+    });
+
+    it("Should enforce a reward type budget over the rolling window", async () => {
+      // 2 MOB of Mission rewards were minted above
+      await updateBudgets([3 * MOB, 0, 0, 0, 0, 0, 0, 0]);
+      await mintReward(backend, MOB);
+
+      try {
+        await mintReward(backend, MOB / 2);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("RewardTypeBudgetExceeded");
+      }
+    });
+
+    it("Should enforce the daily emission over the rolling window", async () => {
+      await updateBudgets([0, 0, 0, 0, 0, 0, 0, 0]);
+      await mintReward(backend, 2 * MOB);
+
+      try {
+        await mintReward(backend, 1);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("DailyEmissionExceeded");
+      }
+
+      const config = await program.account.tokenConfig.fetch(configPda);
+      expect(config.totalMinted.toNumber()).to.equal(DAILY_EMISSION);
+    });
+  });
+});
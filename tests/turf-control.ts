@@ -0,0 +1,357 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { TurfControl } from "../target/types/turf_control";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import { createHash, randomBytes } from "crypto";
+
+describe("Turf Control Program", () => {
+  // Configure the client to use the local cluster
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.TurfControl as Program<TurfControl>;
+
+  // Test accounts
+  let authority: Keypair;
+  let owner: Keypair;
+  let rival: Keypair;
+  let configPda: PublicKey;
+  let mintAuthorityPda: PublicKey;
+  let incomeAuthorityPda: PublicKey;
+  let vaultAuthorityPda: PublicKey;
+  let territoryMint: Keypair;
+  let rivalTerritoryMint: Keypair;
+  let playerSecret: Buffer;
+  let serverSecret: Buffer;
+
+  const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+  const commitment = (secret: Buffer) => Array.from(createHash("sha256").update(secret).digest());
+
+  const territoryPda = (mint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("territory"), mint.toBuffer()],
+      program.programId
+    )[0];
+
+  const vaultPda = (mint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("territory_vault"), mint.toBuffer()],
+      program.programId
+    )[0];
+
+  const attackPda = (mint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("attack"), mint.toBuffer()],
+      program.programId
+    )[0];
+
+  const mintTerritory = async (mint: Keypair, territoryOwner: Keypair, plotId: number) => {
+    await program.methods
+      .mintTerritory({ downtown: {} }, plotId, new anchor.BN(1_000_000))
+      .accounts({
+        config: configPda,
+        mint: mint.publicKey,
+        mintAuthority: mintAuthorityPda,
+        tokenAccount: getAssociatedTokenAddressSync(mint.publicKey, territoryOwner.publicKey),
+        territory: territoryPda(mint.publicKey),
+        payer: authority.publicKey,
+        owner: territoryOwner.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([mint, authority])
+      .rpc();
+  };
+
+  const withdrawAccounts = (mint: PublicKey, signer: PublicKey) => ({
+    territory: territoryPda(mint),
+    mint,
+    vaultTokenAccount: vaultPda(mint),
+    vaultAuthority: vaultAuthorityPda,
+    ownerTokenAccount: getAssociatedTokenAddressSync(mint, signer),
+    owner: signer,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    systemProgram: anchor.web3.SystemProgram.programId,
+  });
+
+  const deposit = async (mint: PublicKey, territoryOwner: Keypair) => {
+    await program.methods
+      .depositTerritory()
+      .accounts({
+        territory: territoryPda(mint),
+        mint,
+        ownerTokenAccount: getAssociatedTokenAddressSync(mint, territoryOwner.publicKey),
+        vaultTokenAccount: vaultPda(mint),
+        vaultAuthority: vaultAuthorityPda,
+        owner: territoryOwner.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([territoryOwner])
+      .rpc();
+  };
+
+  const attack = async (defenderMint: PublicKey) => {
+    playerSecret = randomBytes(32);
+    serverSecret = randomBytes(32);
+    await program.methods
+      .attackTerritory({
+        commitReveal: {
+          playerCommitment: commitment(playerSecret),
+          serverCommitment: commitment(serverSecret),
+        },
+      })
+      .accounts({
+        config: configPda,
+        attackerTerritory: territoryPda(rivalTerritoryMint.publicKey),
+        defenderTerritory: territoryPda(defenderMint),
+        pendingAttack: attackPda(defenderMint),
+        attacker: rival.publicKey,
+        gameServer: authority.publicKey,
+        vrfResult: null,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([rival, authority])
+      .rpc();
+  };
+
+  before(async () => {
+    authority = Keypair.generate();
+    owner = Keypair.generate();
+    rival = Keypair.generate();
+    territoryMint = Keypair.generate();
+    rivalTerritoryMint = Keypair.generate();
+
+    for (const account of [authority, owner, rival]) {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(account.publicKey, 2 * LAMPORTS_PER_SOL)
+      );
+    }
+
+    [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      program.programId
+    );
+    [mintAuthorityPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_authority")],
+      program.programId
+    );
+    [incomeAuthorityPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("income_authority")],
+      program.programId
+    );
+    [vaultAuthorityPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault_authority")],
+      program.programId
+    );
+  });
+
+  describe("Initialization", () => {
+    it("Should initialize turf control and mint territories", async () => {
+      const turfCollection = await createMint(provider.connection, authority, authority.publicKey, null, 0);
+      const mobMint = await createMint(provider.connection, authority, authority.publicKey, null, 9);
+
+      await program.methods
+        .initialize()
+        .accounts({
+          config: configPda,
+          mintAuthority: mintAuthorityPda,
+          incomeAuthority: incomeAuthorityPda,
+          turfCollection,
+          mobMint,
+          authority: authority.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+      await mintTerritory(territoryMint, owner, 1);
+      await mintTerritory(rivalTerritoryMint, rival, 2);
+
+      const territory = await program.account.territory.fetch(territoryPda(territoryMint.publicKey));
+      expect(territory.owner.toString()).to.equal(owner.publicKey.toString());
+      expect(territory.isEscrowed).to.be.false;
+    });
+  });
+
+  describe("Territory Escrow", () => {
+    it("Should not attack a territory that is not escrowed", async () => {
+      try {
+        await attack(territoryMint.publicKey);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NotEscrowed");
+      }
+    });
+
+    it("Should deposit a territory into the vault", async () => {
+      await deposit(territoryMint.publicKey, owner);
+
+      const vault = await getAccount(provider.connection, vaultPda(territoryMint.publicKey));
+      expect(Number(vault.amount)).to.equal(1);
+      const ownerAccount = await getAccount(
+        provider.connection,
+        getAssociatedTokenAddressSync(territoryMint.publicKey, owner.publicKey)
+      );
+      expect(Number(ownerAccount.amount)).to.equal(0);
+
+      const territory = await program.account.territory.fetch(territoryPda(territoryMint.publicKey));
+      expect(territory.isEscrowed).to.be.true;
+    });
+
+    it("Should not let a non-owner withdraw an escrowed territory", async () => {
+      try {
+        await program.methods
+          .withdrawTerritory()
+          .accounts(withdrawAccounts(territoryMint.publicKey, rival.publicKey))
+          .signers([rival])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("NotOwner");
+      }
+    });
+
+    it("Should withdraw a territory back to its owner and close the vault", async () => {
+      await program.methods
+        .withdrawTerritory()
+        .accounts(withdrawAccounts(territoryMint.publicKey, owner.publicKey))
+        .signers([owner])
+        .rpc();
+
+      const ownerAccount = await getAccount(
+        provider.connection,
+        getAssociatedTokenAddressSync(territoryMint.publicKey, owner.publicKey)
+      );
+      expect(Number(ownerAccount.amount)).to.equal(1);
+      expect(await provider.connection.getAccountInfo(vaultPda(territoryMint.publicKey))).to.be.null;
+
+      const territory = await program.account.territory.fetch(territoryPda(territoryMint.publicKey));
+      expect(territory.isEscrowed).to.be.false;
+    });
+
+    it("Should keep a territory in escrow while it is under attack", async () => {
+      // Shorten the attack window so the attack can be resolved below
+      await program.methods
+        .updateConfig(null, new anchor.BN(1), null, null, null, null)
+        .accounts({
+          config: configPda,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+      await deposit(territoryMint.publicKey, owner);
+      await attack(territoryMint.publicKey);
+
+      const territory = await program.account.territory.fetch(territoryPda(territoryMint.publicKey));
+      expect(territory.isUnderAttack).to.be.true;
+
+      try {
+        await program.methods
+          .withdrawTerritory()
+          .accounts(withdrawAccounts(territoryMint.publicKey, owner.publicKey))
+          .signers([owner])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("TerritoryUnderAttack");
+      }
+    });
+  });
+
+  describe("Attack Resolution", () => {
+    const lockSeed = () =>
+      program.methods
+        .lockAttackSeed()
+        .accounts({
+          pendingAttack: attackPda(territoryMint.publicKey),
+          slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+        })
+        .rpc();
+
+    const resolve = (secrets: { playerSecret: number[]; serverSecret: number[] }) =>
+      program.methods
+        .resolveAttack(secrets)
+        .accounts({
+          config: configPda,
+          pendingAttack: attackPda(territoryMint.publicKey),
+          attackerTerritory: territoryPda(rivalTerritoryMint.publicKey),
+          defenderTerritory: territoryPda(territoryMint.publicKey),
+          attacker: rival.publicKey,
+          vrfResult: null,
+          slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+        })
+        .rpc();
+
+    it("Should lock the seed slot once after the attack window", async () => {
+      await sleep(2000);
+      await lockSeed();
+
+      const pendingAttack = await program.account.pendingAttack.fetch(attackPda(territoryMint.publicKey));
+      expect(pendingAttack.seedSlot.toNumber()).to.be.above(0);
+
+      try {
+        await lockSeed();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("SeedSlotAlreadyLocked");
+      }
+
+      while ((await provider.connection.getSlot()) <= pendingAttack.seedSlot.toNumber()) {
+        await sleep(200);
+      }
+    });
+
+    it("Should not score a bad reveal as a failed attack", async () => {
+      try {
+        await resolve({
+          playerSecret: Array.from(playerSecret),
+          serverSecret: Array.from(randomBytes(32)),
+        });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidReveal");
+      }
+
+      const territory = await program.account.territory.fetch(territoryPda(territoryMint.publicKey));
+      expect(territory.isUnderAttack).to.be.true;
+    });
+
+    it("Should resolve the attack from the revealed secrets", async () => {
+      await resolve({
+        playerSecret: Array.from(playerSecret),
+        serverSecret: Array.from(serverSecret),
+      });
+
+      expect(await provider.connection.getAccountInfo(attackPda(territoryMint.publicKey))).to.be.null;
+
+      const defender = await program.account.territory.fetch(territoryPda(territoryMint.publicKey));
+      const attacker = await program.account.territory.fetch(territoryPda(rivalTerritoryMint.publicKey));
+      expect(defender.isUnderAttack).to.be.false;
+      expect(attacker.lastAttackTime.toNumber()).to.be.above(0);
+
+      if (attacker.attackWins === 1) {
+        expect(defender.owner.toString()).to.equal(rival.publicKey.toString());
+        expect(defender.defenseWins).to.equal(0);
+      } else {
+        expect(defender.owner.toString()).to.equal(owner.publicKey.toString());
+        expect(defender.defenseWins).to.equal(1);
+      }
+      // Either way the NFT stays escrowed for whoever now owns the territory
+      expect(defender.isEscrowed).to.be.true;
+    });
+  });
+});